@@ -15,6 +15,11 @@ contract Pair is IPair {
     uint8 public constant BUY = 1;
     uint8 public constant SELL = 1;
     uint256 public constant PRICE_MULTIPLIER = 10 ** 30;
+    uint256 public constant MAX_OWNER_GRIDS = 1000;
+    // max grids placed by one placeGridOrdersBatch call
+    uint256 public constant MAX_BATCH_GRIDS = 10;
+    // max orders scanned by one getActiveOrders call
+    uint256 public constant MAX_QUERY_ORDERS = 1000;
 
     /// @inheritdoc IPair
     address public immutable override factory;
@@ -38,6 +43,31 @@ contract Pair is IPair {
     /// @inheritdoc IPair
     uint256 public override protocolFees = 0;
 
+    /// @inheritdoc IPair
+    uint256 public override baseReserve;
+    /// @inheritdoc IPair
+    uint256 public override quoteReserve;
+
+    /// @inheritdoc IPair
+    uint32 public override maxActiveGrids;
+    /// @inheritdoc IPair
+    uint32 public override activeGrids;
+    /// @inheritdoc IPair
+    uint32 public override profitSettlementDelay;
+    /// @inheritdoc IPair
+    uint16 public override maxOrdersPerSide;
+    /// @inheritdoc IPair
+    uint128 public override minPriceGap;
+
+    // trading fee of fills whose quote volume >= minVol, not higher than pair fee
+    struct FeeTier {
+        // 0 means the tier is disabled
+        uint128 minVol;
+        uint24 fee;
+    }
+    // ascending by minVol
+    FeeTier[3] public feeTiers;
+
     /// order
     struct Order {
         // order price
@@ -57,10 +87,34 @@ contract Pair is IPair {
 
     struct GridConfig {
         address owner;
-        bool compound;
-        uint32 orders;
+        bool compound; // quote of filled sell orders is all reversed
+        bool bidCompound; // lp fees of filled buy orders stay in the orders
+        bool paused; // paused grid can not be filled
+        bool blockSelfFill; // grid owner can not fill the grid
+        bool ceilQuota; // round up the reverse quota, so the reverse order buys back full baseAmt
+        bool askNoReverse; // filled sell orders are not bought back
+        bool bidNoReverse; // filled buy orders are not sold back
+        bool amountInQuote; // baseAmt is the quote amount of every buy order
+        bool makerFee; // maker pays the trading fee
         uint128 profits; // quote token
+        uint128 fees; // quote token, lp fees of the non-compound grid
         uint96 baseAmt;
+        uint96 maxFillAmt; // max base amount of one order filled by one fill, 0 means no limit
+        uint16 keeperTipBps; // share of the spread profit left to the taker, in basis points
+        uint16 fillCooldown; // min blocks between two fills of the same order, 0 means no limit
+        uint32 ownerIndex; // index of the grid in ownerGrids[owner]
+        uint64 profitTime; // timestamp of the latest pending profits
+        uint64 startTime; // grid can not be filled before startTime
+        uint128 harvestThreshold; // profits and fees are sent to owner once reach it, 0 means disabled
+        address beneficiary; // receives beneficiaryBps of every profits payout
+        uint16 beneficiaryBps; // share of the profits paid to beneficiary, in basis points
+        uint32 orders;
+        uint96 quotaBase; // reverse quota reference of the non-compound grid, 0 means baseAmt
+        uint128 maturingProfits; // profits and fees settled at profitTime of their bucket + delay
+        uint128 pendingProfits; // profits and fees still taking new amounts, settled at profitTime + delay
+        uint64 pendingTime; // timestamp of the first pending profits
+        uint64 maturingTime; // timestamp of the latest maturing profits
+        bytes32 label; // informational only
     }
 
     uint64 public nextGridId = 1;
@@ -70,6 +124,18 @@ contract Pair is IPair {
 
     mapping(uint64 gridId => GridConfig) public gridConfigs;
 
+    // grids of every owner which are not canceled
+    mapping(address owner => uint64[]) private ownerGrids;
+
+    // how many times the order has been filled, kept after the order is canceled
+    mapping(uint64 orderId => uint32) public orderFills;
+
+    // the latest block the order is filled, only recorded for grids with fill cooldown
+    mapping(uint64 orderId => uint64) public lastFillBlock;
+
+    // addresses the owner allows to place grids on its behalf
+    mapping(address owner => mapping(address funder => bool)) public gridFunders;
+
     constructor() {
         uint24 _fee;
         address _base;
@@ -79,10 +145,30 @@ contract Pair is IPair {
         (factory, _base, _quote, _fee, _feeProtocol) = IPairDeployer(msg.sender).parameters();
         slot0.fee = _fee;
         slot0.feeProtocol = _feeProtocol;
+        slot0.unlocked = true;
         baseToken = Currency.wrap(_base);
         quoteToken = Currency.wrap(_quote);
     }
 
+    // private methods are used instead of inlining into modifier, which is copied into each method
+    function lockPair() private {
+        if (!slot0.unlocked) {
+            revert Locked();
+        }
+        slot0.unlocked = false;
+    }
+
+    function unlockPair() private {
+        slot0.unlocked = true;
+    }
+
+    // prevents reentrancy from token transfers
+    modifier lock() {
+        lockPair();
+        _;
+        unlockPair();
+    }
+
     // @inheritdoc IPair
     function fee() external view returns (uint24) {
         return slot0.fee;
@@ -102,6 +188,14 @@ contract Pair is IPair {
         uint16 asks;
         uint16 bids;
         bool compound;
+        // baseAmount is the quote amount of every buy order, only for grids without sell orders
+        bool amountInQuote;
+        // maker pays the trading fee from the grid orders, taker pays or gets the quote volume exactly
+        bool makerFee;
+        // the grid can not be filled before startTime, 0 means fillable at once
+        uint64 startTime;
+        // client defined tag of the grid, informational only
+        bytes32 label;
     }
 
     function validateGridOrderParam(
@@ -115,9 +209,14 @@ contract Pair is IPair {
         uint256 bids = params.bids;
 
         // grid price
-        if (sellPrice0 == 0 || buyPrice0 == 0 || sellPrice0 <= buyPrice0) {
+        if (sellPrice0 == 0 || buyPrice0 == 0) {
             revert InvalidGridPrice();
         }
+        // the lowest sell price must be higher than the highest buy price,
+        // the price of the empty side is not used
+        if (asks > 0 && bids > 0 && sellPrice0 <= buyPrice0) {
+            revert CrossedGrid();
+        }
         if (
             sellPrice0 > uint256(type(uint160).max) ||
             buyPrice0 > uint256(type(uint160).max) ||
@@ -136,6 +235,13 @@ contract Pair is IPair {
         if (asks == 0 && bids == 0) {
             revert ZeroGridOrderCount();
         }
+        if (params.amountInQuote) {
+            if (asks > 0) {
+                revert InvalidParam();
+            }
+            // make sure the highest buy order buys some base token
+            calcBaseAmount(params.baseAmount, buyPrice0);
+        }
 
         // grid price gap
         uint96 perBaseAmt = params.baseAmount;
@@ -148,7 +254,11 @@ contract Pair is IPair {
             ) {
                 revert InvalidGapPrice();
             }
-            if (bids > 1 && uint256(bids - 1) * buyGap >= buyPrice0) {
+            // buy orders at one price, as the quote amount check is skipped if amountInQuote
+            if (
+                bids > 1 &&
+                (buyGap == 0 || uint256(bids - 1) * buyGap >= buyPrice0)
+            ) {
                 revert InvalidGapPrice();
             }
             baseAmt = uint256(perBaseAmt) * uint256(asks);
@@ -156,12 +266,61 @@ contract Pair is IPair {
                 revert ExceedMaxAmount();
             }
         }
-        // make sure the highest sell order quote amount not overflow
+        // quote amount of adjacent orders must differ by one unit of quote token at least
+        if (
+            asks > 1 && (uint256(perBaseAmt) * sellGap) / PRICE_MULTIPLIER == 0
+        ) {
+            revert DegenerateLadder();
+        }
+        if (
+            bids > 1 &&
+            !params.amountInQuote &&
+            (uint256(perBaseAmt) * buyGap) / PRICE_MULTIPLIER == 0
+        ) {
+            revert DegenerateLadder();
+        }
         if (asks > 0) {
+            // make sure the highest sell order quote amount not overflow
             calcQuoteAmount(
                 uint256(perBaseAmt),
                 sellPrice0 + uint256(asks - 1) * sellGap
             );
+            // make sure the lowest reverse price of sell orders can buy back base token
+            if (
+                (uint256(perBaseAmt) * (sellPrice0 - sellGap)) /
+                    PRICE_MULTIPLIER ==
+                0
+            ) {
+                revert PriceTooLowForOrderSize();
+            }
+        }
+    }
+
+    // preview the price range of the grid before placing it,
+    // highPrice is the highest sell price and lowPrice is the lowest buy price, including reverse orders
+    function quoteGridRange(
+        GridOrderParam calldata params
+    ) public pure returns (uint256 lowPrice, uint256 highPrice) {
+        validateGridOrderParam(params);
+
+        // the range covers the order prices and the reverse prices of both sides
+        lowPrice = type(uint256).max;
+        if (params.asks > 0) {
+            highPrice =
+                params.sellPrice0 +
+                uint256(params.asks - 1) *
+                params.sellGap;
+            lowPrice = params.sellPrice0 - params.sellGap;
+        }
+        if (params.bids > 0) {
+            uint256 price = params.buyPrice0 + params.buyGap;
+            if (price > highPrice) {
+                highPrice = price;
+            }
+            price = params.buyPrice0 - uint256(params.bids - 1) * params.buyGap;
+            if (price < lowPrice) {
+                lowPrice = price;
+            }
         }
     }
 
@@ -169,9 +328,113 @@ contract Pair is IPair {
         return orderId & AskOderMask > 0;
     }
 
-    function placeGridOrders(GridOrderParam calldata params) public {
+    function placeGridOrders(GridOrderParam calldata params) public lock {
+        (uint256 baseAmt, uint256 quoteAmt) = createGrid(msg.sender, params);
+
+        settle(baseToken, 0, baseAmt);
+        settle(quoteToken, 0, quoteAmt);
+    }
+
+    // place grids in one call with ids in paramsList order, the deposits of all grids
+    // are transferred at once; any invalid grid reverts the whole batch
+    function placeGridOrdersBatch(
+        GridOrderParam[] calldata paramsList
+    ) public lock {
+        if (paramsList.length == 0 || paramsList.length > MAX_BATCH_GRIDS) {
+            revert InvalidParam();
+        }
+
+        uint256 totalBaseAmt = 0;
+        uint256 totalQuoteAmt = 0;
+        for (uint i = 0; i < paramsList.length; ++i) {
+            (uint256 baseAmt, uint256 quoteAmt) = createGrid(
+                msg.sender,
+                paramsList[i]
+            );
+            totalBaseAmt += baseAmt;
+            totalQuoteAmt += quoteAmt;
+        }
+
+        settle(baseToken, 0, totalBaseAmt);
+        settle(quoteToken, 0, totalQuoteAmt);
+    }
+
+    // allow or disallow funder to place grids owned by the caller, the grids count
+    // against the caller's MAX_OWNER_GRIDS
+    function setGridFunder(address funder, bool allowed) public {
+        gridFunders[msg.sender][funder] = allowed;
+        emit SetGridFunder(msg.sender, funder, allowed);
+    }
+
+    // place a grid owned by owner, the caller deposits the tokens and must be allowed
+    // by the owner, only the owner can change, sweep or cancel the grid
+    function placeGridOrdersFor(
+        address owner,
+        GridOrderParam calldata params
+    ) public lock {
+        if (owner == address(0)) {
+            revert InvalidParam();
+        }
+        if (owner != msg.sender && !gridFunders[owner][msg.sender]) {
+            revert NotGridFunder();
+        }
+        (uint256 baseAmt, uint256 quoteAmt) = createGrid(owner, params);
+
+        settle(baseToken, 0, baseAmt);
+        settle(quoteToken, 0, quoteAmt);
+    }
+
+    // cancel the grid orders in idList and place a new grid with params in one call,
+    // only the difference between refund and deposit is transferred; unswept profits
+    // and fees of the fully cancelled grids move to the new grid
+    function rolloverGridOrders(
+        uint64[] calldata idList,
+        GridOrderParam calldata params
+    ) public lock {
+        uint64[] memory gridIds = new uint64[](idList.length);
+        for (uint i = 0; i < idList.length; ++i) {
+            gridIds[i] = orderGridId(idList[i]);
+        }
+        (uint256 refundBaseAmt, uint256 refundQuoteAmt) = cancelGridOrderList(
+            idList
+        );
+        (uint256 baseAmt, uint256 quoteAmt) = createGrid(msg.sender, params);
+        carryGridProfits(gridIds, nextGridId - 1);
+
+        settle(baseToken, refundBaseAmt, baseAmt);
+        settle(quoteToken, refundQuoteAmt, quoteAmt);
+    }
+
+    // create grid orders, returns the base and quote amount should be deposited
+    function createGrid(
+        address owner,
+        GridOrderParam calldata params
+    ) private returns (uint256 baseAmt, uint256 quoteAmt) {
         // validate grid params
         validateGridOrderParam(params);
+        {
+            uint32 grids = activeGrids + 1;
+            if (maxActiveGrids > 0 && grids > maxActiveGrids) {
+                revert ExceedMaxActiveGrids();
+            }
+            activeGrids = grids;
+
+            uint16 maxOrders = maxOrdersPerSide;
+            if (
+                maxOrders > 0 &&
+                (params.asks > maxOrders || params.bids > maxOrders)
+            ) {
+                revert ExceedMaxOrdersPerSide();
+            }
+
+            uint256 minGap = minPriceGap;
+            if (
+                (params.asks > 1 && params.sellGap < minGap) ||
+                (params.bids > 1 && params.buyGap < minGap)
+            ) {
+                revert PriceGapTooSmall();
+            }
+        }
         uint64 gridId = nextGridId;
         uint64 askOrderId = 0;
         uint64 bidOrderId = 0;
@@ -202,18 +465,12 @@ contract Pair is IPair {
                     sellPrice0 += sellGap;
                 }
             }
-            IERC20Minimal(Currency.unwrap(baseToken)).safeTransferFrom(
-                msg.sender,
-                address(this),
-                params.asks * params.baseAmount
-            );
+            baseAmt = params.asks * params.baseAmount;
         }
 
         if (params.bids > 0) {
             uint256 buyPrice0 = params.buyPrice0;
             uint256 buyGap = params.buyGap;
-            uint256 perBaseAmt = params.baseAmount;
-            uint256 quoteAmt = 0;
             // create bid orders
             bidOrderId = nextBidOrderId;
 
@@ -225,7 +482,9 @@ contract Pair is IPair {
 
                 for (uint i = 0; i < params.bids; ) {
                     uint256 price = buyPrice0 - i * buyGap;
-                    uint256 amt = calcQuoteAmount(perBaseAmt, price);
+                    uint256 amt = params.amountInQuote
+                        ? params.baseAmount
+                        : calcQuoteAmount(params.baseAmount, price);
 
                     bidOrders[bidOrderId] = Order({
                         gridId: gridId,
@@ -241,15 +500,9 @@ contract Pair is IPair {
                     ++bidOrderId;
                 }
             }
-            // transfer base/quote tokens
             if (quoteAmt > type(uint160).max) {
                 revert ExceedMaxAmount();
             }
-            IERC20Minimal(Currency.unwrap(quoteToken)).safeTransferFrom(
-                msg.sender,
-                address(this),
-                quoteAmt
-            );
         }
 
         unchecked {
@@ -258,18 +511,54 @@ contract Pair is IPair {
         }
         // initialize owner's grid config
         gridConfigs[uint64(gridId)] = GridConfig({
-            owner: msg.sender,
+            owner: owner,
             orders: uint32(params.asks + params.bids),
             profits: 0,
+            fees: 0,
             compound: params.compound,
-            baseAmt: params.baseAmount
+            bidCompound: params.compound,
+            paused: false,
+            blockSelfFill: false,
+            ceilQuota: false,
+            askNoReverse: false,
+            bidNoReverse: false,
+            amountInQuote: params.amountInQuote,
+            makerFee: params.makerFee,
+            baseAmt: params.baseAmount,
+            maxFillAmt: 0,
+            keeperTipBps: 0,
+            fillCooldown: 0,
+            harvestThreshold: 0,
+            beneficiary: address(0),
+            beneficiaryBps: 0,
+            quotaBase: 0,
+            ownerIndex: addOwnerGrid(owner, gridId),
+            profitTime: 0,
+            maturingProfits: 0,
+            pendingProfits: 0,
+            pendingTime: 0,
+            maturingTime: 0,
+            startTime: params.startTime,
+            label: params.label
         });
 
+        emitGridOrderCreated(owner, gridId, askOrderId, bidOrderId, params);
+        emit GridDeposit(gridId, baseAmt, quoteAmt);
+    }
+
+    // avoid stacks too deep
+    function emitGridOrderCreated(
+        address owner,
+        uint64 gridId,
+        uint64 askOrderId,
+        uint64 bidOrderId,
+        GridOrderParam calldata params
+    ) private {
         emit GridOrderCreated(
-            msg.sender,
+            owner,
             params.asks,
             params.bids,
-            uint64(gridId),
+            gridId,
             askOrderId,
             bidOrderId,
             params.sellPrice0,
@@ -279,6 +568,38 @@ contract Pair is IPair {
             params.baseAmount,
             params.compound
         );
+        if (params.label != bytes32(0)) {
+            emit GridLabel(gridId, params.label);
+        }
+    }
+
+    // transfer the net amount of token between msg.sender and pair:
+    // amountOut is owed to msg.sender, amountIn is owed by msg.sender
+    function settle(
+        Currency token,
+        uint256 amountOut,
+        uint256 amountIn
+    ) private {
+        if (token == baseToken) {
+            baseReserve = baseReserve + amountIn - amountOut;
+        } else {
+            quoteReserve = quoteReserve + amountIn - amountOut;
+        }
+
+        if (amountOut > amountIn) {
+            token.transfer(msg.sender, amountOut - amountIn);
+        } else if (amountIn > amountOut) {
+            // reserves are only increased by what the pair really receives
+            uint256 balance = token.balanceOfSelf();
+            IERC20Minimal(Currency.unwrap(token)).safeTransferFrom(
+                msg.sender,
+                address(this),
+                amountIn - amountOut
+            );
+            if (token.balanceOfSelf() < balance + amountIn - amountOut) {
+                revert DepositShortfall();
+            }
+        }
     }
 
     function calcQuoteAmount(
@@ -298,6 +619,24 @@ contract Pair is IPair {
         return amt;
     }
 
+    // same as calcQuoteAmount, but round up
+    function calcQuoteAmountUp(
+        uint256 baseAmt,
+        uint256 price
+    ) public pure returns (uint256) {
+        uint256 amt = 0;
+        unchecked {
+            amt = ((baseAmt) * (price) + PRICE_MULTIPLIER - 1) / PRICE_MULTIPLIER;
+        }
+        if (amt == 0) {
+            revert ZeroQuoteAmt();
+        }
+        if (amt >= uint256(type(uint96).max)) {
+            revert ExceedQuoteAmt();
+        }
+        return amt;
+    }
+
     function calcBaseAmount(
         uint256 quoteAmt,
         uint256 price
@@ -315,25 +654,265 @@ contract Pair is IPair {
         return amt;
     }
 
+    // same as calcBaseAmount, but round up
+    function calcBaseAmountUp(
+        uint256 quoteAmt,
+        uint256 price
+    ) public pure returns (uint256) {
+        uint256 amt = 0;
+        unchecked {
+            amt = ((quoteAmt) * PRICE_MULTIPLIER + price - 1) / price;
+        }
+        if (amt == 0) {
+            revert ZeroBaseAmt();
+        }
+        if (amt >= uint256(type(uint96).max)) {
+            revert ExceedBaseAmt();
+        }
+        return amt;
+    }
+
     // amount is always quote amount
+    // amount is the quote volume of a fill, which is less than uint96 max
     function collectProtocolFee(
         uint256 amount
-    ) public returns (uint256, uint256) {
+    ) private returns (uint256, uint256) {
         uint256 totalFee;
         uint256 protoFee = 0;
 
+        // round up against the payer of the fee: the taker, or the maker of a makerFee
+        // grid, who pays it from the proceeds; the extra unit goes to the lp fee and the
+        // protocol fee like the rest of the fee
         unchecked {
-            totalFee = (uint256(slot0.fee) * uint256(amount)) / 1000000;
-            uint8 feeProto = slot0.feeProtocol;
-            if (feeProto > 0) {
-                protoFee = totalFee / uint256(feeProto);
-                protocolFees += uint128(protoFee);
-            }
+            totalFee =
+                (tradingFee(amount) * uint256(amount) + 999999) /
+                1000000;
+        }
+        uint8 feeProto = slot0.feeProtocol;
+        if (feeProto > 0) {
+            protoFee = totalFee / uint256(feeProto);
+            // accumulated protocol fees are checked
+            protocolFees += uint128(protoFee);
         }
 
         return (totalFee, totalFee - protoFee);
     }
 
+    // fee rate of the quote volume, the fee of the highest reached tier or pair fee
+    function tradingFee(uint256 vol) private view returns (uint256 rate) {
+        rate = slot0.fee;
+        for (uint i = 0; i < 3; ++i) {
+            FeeTier memory tier = feeTiers[i];
+            if (tier.minVol == 0 || vol < tier.minVol) {
+                break;
+            }
+            rate = tier.fee;
+        }
+    }
+
+    // revert if the order was filled within the grid's fill cooldown
+    function checkFillCooldown(uint64 gridId, uint64 id) private {
+        uint256 cooldown = gridConfigs[gridId].fillCooldown;
+        if (cooldown == 0) {
+            return;
+        }
+        uint256 last = lastFillBlock[id];
+        if (last > 0 && block.number < last + cooldown) {
+            revert FillCooldown();
+        }
+        lastFillBlock[id] = uint64(block.number);
+    }
+
+    // informational only, saturates at uint32 max instead of reverting the fill
+    function countOrderFill(uint64 id) private {
+        uint32 fills = orderFills[id];
+        if (fills < type(uint32).max) {
+            unchecked {
+                orderFills[id] = fills + 1;
+            }
+        }
+    }
+
+    function addGridProfits(uint64 gridId, uint256 amt) private {
+        if (amt == 0) {
+            return;
+        }
+        GridConfig storage conf = gridConfigs[gridId];
+        conf.profits += uint128(amt);
+        addPendingProfits(conf, amt);
+    }
+
+    // spread profit of a non-compound grid, the keeper tip is left to the taker
+    function addSpreadProfits(
+        uint64 gridId,
+        uint256 amt
+    ) private returns (uint256 tip) {
+        tip = (amt * gridConfigs[gridId].keeperTipBps) / 10000;
+        addGridProfits(gridId, amt - tip);
+    }
+
+    // fees are kept apart from profits, and swept with profits
+    function addGridFees(uint64 gridId, uint256 amt) private {
+        if (amt == 0) {
+            return;
+        }
+        GridConfig storage conf = gridConfigs[gridId];
+        conf.fees += uint128(amt);
+        addPendingProfits(conf, amt);
+    }
+
+    // new profits and fees wait for the settlement delay in two buckets: the pending
+    // bucket takes new amounts for one delay from its first amount, then it becomes the
+    // maturing bucket, which settles one delay after its latest amount. Every amount waits
+    // between one and two delays, whatever later fills do
+    function addPendingProfits(GridConfig storage conf, uint256 amt) private {
+        uint256 delay = profitSettlementDelay;
+        if (delay == 0) {
+            return;
+        }
+        if (
+            conf.pendingProfits > 0 &&
+            block.timestamp >= conf.pendingTime + delay
+        ) {
+            // the previous maturing bucket is settled by now
+            conf.maturingProfits = conf.pendingProfits;
+            conf.maturingTime = conf.profitTime;
+            conf.pendingProfits = 0;
+        }
+        if (conf.pendingProfits == 0) {
+            conf.pendingTime = uint64(block.timestamp);
+        }
+        conf.pendingProfits += uint128(amt);
+        conf.profitTime = uint64(block.timestamp);
+    }
+
+    // profits and fees of the grid still within the settlement delay
+    function unsettledProfits(
+        GridConfig storage conf
+    ) private view returns (uint256 amt) {
+        uint256 delay = profitSettlementDelay;
+        if (delay == 0) {
+            return 0;
+        }
+        if (block.timestamp < conf.profitTime + delay) {
+            amt = conf.pendingProfits;
+        }
+        if (block.timestamp < conf.maturingTime + delay) {
+            amt += conf.maturingProfits;
+        }
+        uint256 total = uint256(conf.profits) + conf.fees;
+        if (amt > total) {
+            amt = total;
+        }
+    }
+
+    // send profits and fees to the grid owner once they reach the harvest threshold,
+    // disabled while profits must wait for the settlement delay; a payout that fails,
+    // e.g. to a blacklisted address, is kept in the grid profits instead of failing the fill
+    function autoHarvest(uint64 gridId) private {
+        GridConfig storage conf = gridConfigs[gridId];
+        uint256 threshold = conf.harvestThreshold;
+        if (threshold == 0 || profitSettlementDelay > 0) {
+            return;
+        }
+        uint256 amt = uint256(conf.profits) + conf.fees;
+        if (amt < threshold) {
+            return;
+        }
+
+        uint256 share = (amt * conf.beneficiaryBps) / 10000;
+        uint256 kept = 0;
+        if (share > 0 && !quoteToken.tryTransfer(conf.beneficiary, share)) {
+            kept = share;
+        }
+        if (!quoteToken.tryTransfer(conf.owner, amt - share)) {
+            kept += amt - share;
+        }
+        conf.profits = uint128(kept);
+        conf.fees = 0;
+        quoteReserve -= amt - kept;
+    }
+
+    // harvest the grids of the filled orders, called after settle so the quote paid
+    // by the taker is already in the pair
+    function autoHarvestOrders(uint64[] calldata idList) private {
+        for (uint i = 0; i < idList.length; ++i) {
+            autoHarvest(orderGridId(idList[i]));
+        }
+    }
+
+    function orderGridId(uint64 id) private view returns (uint64) {
+        return
+            isAskGridOrder(id) ? askOrders[id].gridId : bidOrders[id].gridId;
+    }
+
+    // pay profits and fees out of the pair, the beneficiary's share first
+    function payProfits(
+        GridConfig storage conf,
+        uint256 amt,
+        address to
+    ) private {
+        quoteReserve -= amt;
+        uint256 share = (amt * conf.beneficiaryBps) / 10000;
+        if (share > 0) {
+            quoteToken.transfer(conf.beneficiary, share);
+        }
+        quoteToken.transfer(to, amt - share);
+    }
+
+    // max quote volume a buy order with quoteAmt can fill, the fee is counted at pair fee
+    // if maker pays the fee
+    function fillableQuote(
+        uint64 gridId,
+        uint256 quoteAmt
+    ) private view returns (uint256) {
+        if (!gridConfigs[gridId].makerFee) {
+            return quoteAmt;
+        }
+        return (quoteAmt * 1000000) / (1000000 + uint256(slot0.fee));
+    }
+
+    // if the reverse orders of the grid's sell orders (askOrder) or buy orders are enabled
+    function reverseEnabled(
+        uint64 gridId,
+        bool askOrder
+    ) private view returns (bool) {
+        GridConfig storage conf = gridConfigs[gridId];
+        return !(askOrder ? conf.askNoReverse : conf.bidNoReverse);
+    }
+
+    // revert if the reverse orders of the grid's sell orders (askOrder) or buy orders are disabled,
+    // only for single order fills, batch fills skip the orders like empty orders
+    function checkReverseEnabled(uint64 gridId, bool askOrder) private view {
+        if (!reverseEnabled(gridId, askOrder)) {
+            revert ReverseDisabled();
+        }
+    }
+
+    // revert if the grid orders can not be filled by taker now,
+    // returns the base amount capped by the grid's max fill amount
+    function checkGridFillable(
+        uint64 gridId,
+        address taker,
+        uint256 amt
+    ) private view returns (uint256) {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.paused) {
+            revert GridPaused();
+        }
+        if (block.timestamp < conf.startTime) {
+            revert GridNotStarted();
+        }
+        if (conf.blockSelfFill && conf.owner == taker) {
+            revert SelfFill();
+        }
+        uint256 maxFillAmt = conf.maxFillAmt;
+        if (maxFillAmt > 0 && amt > maxFillAmt) {
+            return maxFillAmt;
+        }
+        return amt;
+    }
+
     function fillAskOrder(
         address taker,
         uint64 id,
@@ -357,24 +936,32 @@ contract Pair is IPair {
         } else {
             order = bidOrders[id];
             // rev amount is base token
-            if (order.revAmount == 0) {
+            if (
+                order.revAmount == 0 || !reverseEnabled(order.gridId, false)
+            ) {
                 return (0, 0);
             }
             orderBaseAmt = order.revAmount;
             orderQuoteAmt = order.amount;
             sellPrice = order.revPrice;
         }
+        amt = checkGridFillable(order.gridId, taker, amt);
 
         if (amt > orderBaseAmt) {
             amt = orderBaseAmt;
         }
-        uint256 vol = calcQuoteAmount(amt, uint256(sellPrice)); // quoteVol = filled * price
+        // quoteVol = filled * price, round up as the taker pays
+        uint256 vol = calcQuoteAmountUp(amt, uint256(sellPrice));
         (uint256 totalFee, uint256 lpFee) = collectProtocolFee(vol);
         unchecked {
             if (vol + totalFee > type(uint96).max) {
                 revert ExceedQuoteAmt();
             }
         }
+        // maker pays the fee from the proceeds, so taker pays the volume only
+        if (gridConfigs[order.gridId].makerFee) {
+            vol -= totalFee;
+        }
 
         unchecked {
             orderBaseAmt -= amt;
@@ -388,20 +975,22 @@ contract Pair is IPair {
                     revert ExceedQuoteAmt();
                 }
             } else {
-                uint256 base = gridConfigs[gridId].baseAmt;
-                uint256 buyPrice = isAsk ? order.revPrice : order.price;
-                uint256 quota = calcQuoteAmount(base, buyPrice);
+                uint256 quota = reverseQuota(
+                    gridId,
+                    isAsk ? order.revPrice : order.price
+                );
+                addGridFees(gridId, lpFee);
                 // increase profit if sell quote amount > baseAmt * price
                 unchecked {
                     if (orderQuoteAmt >= quota) {
-                        gridConfigs[gridId].profits += uint128(vol + lpFee);
+                        vol -= addSpreadProfits(gridId, vol);
                     } else {
-                        uint256 rev = orderQuoteAmt + vol + lpFee;
+                        uint256 rev = orderQuoteAmt + vol;
                         if (rev > quota) {
                             orderQuoteAmt = quota;
-                            gridConfigs[gridId].profits += uint128(rev - quota);
+                            vol -= addSpreadProfits(gridId, rev - quota);
                         } else {
-                            orderQuoteAmt += vol + lpFee;
+                            orderQuoteAmt = rev;
                         }
                     }
                 }
@@ -427,6 +1016,8 @@ contract Pair is IPair {
             bidOrders[id].amount = uint96(orderQuoteAmt);
             bidOrders[id].revAmount = uint96(orderBaseAmt);
         }
+        checkFillCooldown(order.gridId, id);
+        countOrderFill(id);
 
         return (amt, vol + totalFee);
     }
@@ -437,9 +1028,12 @@ contract Pair is IPair {
         uint256 amt,
         uint256 maxAmt, // base amount
         uint256 minAmt // base amount
-    ) public {
+    ) public lock {
         if (maxAmt > 0) require(maxAmt >= amt);
         if (minAmt > 0) require(minAmt <= amt);
+        if (!isAskGridOrder(id)) {
+            checkReverseEnabled(bidOrders[id].gridId, false);
+        }
 
         (uint256 filledAmt, uint256 filledVol) = fillAskOrder(msg.sender, id, amt);
 
@@ -448,14 +1042,11 @@ contract Pair is IPair {
         }
 
         if (filledVol > 0) {
-            IERC20Minimal(Currency.unwrap(quoteToken)).safeTransferFrom(
-                msg.sender,
-                address(this),
-                filledVol
-            );
+            settle(quoteToken, 0, filledVol);
             // transfer base token to taker
-            baseToken.transfer(msg.sender, filledAmt);
+            settle(baseToken, filledAmt, 0);
         }
+        autoHarvest(orderGridId(id));
     }
 
     // taker is BUY
@@ -464,7 +1055,7 @@ contract Pair is IPair {
         uint256[] calldata amtList,
         uint256 maxAmt, // base amount
         uint256 minAmt // base amount
-    ) public {
+    ) public lock {
         if (idList.length != amtList.length) {
             revert InvalidParam();
         }
@@ -499,32 +1090,91 @@ contract Pair is IPair {
             revert NotEnoughToFill();
         }
         if (filledVol > 0) {
-            IERC20Minimal(Currency.unwrap(quoteToken)).safeTransferFrom(
-                msg.sender,
-                address(this),
-                filledVol
-            );
+            settle(quoteToken, 0, filledVol);
             // transfer base token to taker
-            baseToken.transfer(msg.sender, filledAmt);
+            settle(baseToken, filledAmt, 0);
         }
+        autoHarvestOrders(idList);
     }
 
-    // amt is base token
-    function fillBidOrder(
-        address taker,
-        uint64 id,
-        uint256 amt
-    ) private returns (uint256, uint256) {
-        // copy order to memory, save gas
-        Order memory order;
-        uint256 buyPrice;
-        uint256 orderBaseAmt; // base token amount of the grid order
-        uint256 orderQuoteAmt; // quote token amount of the grid order
-        bool isAsk = isAskGridOrder(id);
+    // taker is BUY, spend at most quoteAmt (fee included) filling the orders in idList one by one,
+    // idList should be sorted by price ascending to buy the most base token
+    function fillAskOrdersByQuote(
+        uint64[] calldata idList,
+        uint256 quoteAmt,
+        uint256 minAmt // base amount
+    ) public lock {
+        // keep the amount math in askAmountForQuote from overflowing
+        if (quoteAmt > type(uint128).max) {
+            revert InvalidParam();
+        }
+        uint256 filledAmt = 0; // accumulate base amount
+        uint256 filledVol = 0; // accumulate quote amount
 
-        if (isAsk) {
-            order = askOrders[id];
-            if (order.revAmount == 0) {
+        for (uint i = 0; i < idList.length; ++i) {
+            uint256 amt = askAmountForQuote(idList[i], quoteAmt - filledVol);
+            if (amt == 0) {
+                continue;
+            }
+
+            (
+                uint256 filledBaseAmt,
+                uint256 filledQuoteAmtWithFee
+            ) = fillAskOrder(msg.sender, idList[i], amt);
+
+            unchecked {
+                filledAmt += filledBaseAmt;
+                filledVol += filledQuoteAmtWithFee;
+            }
+        }
+
+        if (minAmt > 0 && filledAmt < minAmt) {
+            revert NotEnoughToFill();
+        }
+        if (filledVol > 0) {
+            settle(quoteToken, 0, filledVol);
+            // transfer base token to taker
+            settle(baseToken, filledAmt, 0);
+        }
+        autoHarvestOrders(idList);
+    }
+
+    // base amount of the order can be bought with quoteAmt, the fee is counted at pair fee,
+    // which is not lower than any fee tier
+    function askAmountForQuote(
+        uint64 id,
+        uint256 quoteAmt
+    ) private view returns (uint256 amt) {
+        Order memory order = getGridOrder(id);
+        uint256 price = isAskGridOrder(id) ? order.price : order.revPrice;
+        if (price == 0) {
+            return 0;
+        }
+
+        uint256 vol = (quoteAmt * 1000000) / (1000000 + uint256(slot0.fee));
+        amt = (vol * PRICE_MULTIPLIER) / price;
+        // too small to fill
+        if ((amt * price) / PRICE_MULTIPLIER == 0) {
+            return 0;
+        }
+    }
+
+    // amt is base token
+    function fillBidOrder(
+        address taker,
+        uint64 id,
+        uint256 amt
+    ) private returns (uint256, uint256) {
+        // copy order to memory, save gas
+        Order memory order;
+        uint256 buyPrice;
+        uint256 orderBaseAmt; // base token amount of the grid order
+        uint256 orderQuoteAmt; // quote token amount of the grid order
+        bool isAsk = isAskGridOrder(id);
+
+        if (isAsk) {
+            order = askOrders[id];
+            if (order.revAmount == 0 || !reverseEnabled(order.gridId, true)) {
                 return (0, 0);
             }
             orderBaseAmt = order.amount;
@@ -540,10 +1190,13 @@ contract Pair is IPair {
             orderQuoteAmt = order.amount;
             buyPrice = order.price;
         }
+        amt = checkGridFillable(order.gridId, taker, amt);
+
         uint256 filledVol = calcQuoteAmount(amt, buyPrice);
-        if (filledVol > orderQuoteAmt) {
-            amt = calcBaseAmount(orderQuoteAmt, buyPrice);
-            filledVol = orderQuoteAmt; // calcQuoteAmount(amt, buyPrice);
+        if (filledVol > fillableQuote(order.gridId, orderQuoteAmt)) {
+            filledVol = fillableQuote(order.gridId, orderQuoteAmt);
+            // round up as the taker pays
+            amt = calcBaseAmountUp(filledVol, buyPrice);
         }
         (uint256 totalFee, uint256 lpFee) = collectProtocolFee(filledVol);
         unchecked {
@@ -551,6 +1204,10 @@ contract Pair is IPair {
                 revert ExceedQuoteAmt();
             }
         }
+        // maker pays the fee from the order, so taker gets the volume
+        if (gridConfigs[order.gridId].makerFee) {
+            filledVol += totalFee;
+        }
         unchecked {
             orderBaseAmt += amt;
         }
@@ -558,11 +1215,10 @@ contract Pair is IPair {
         // avoid stacks too deep
         {
             uint64 gridId = order.gridId;
-            if (gridConfigs[gridId].compound) {
+            if (gridConfigs[gridId].bidCompound) {
                 orderQuoteAmt -= filledVol - lpFee; // all quote reverse
             } else {
-                // lpFee into profit
-                gridConfigs[gridId].profits += uint128(lpFee);
+                addGridFees(gridId, lpFee);
                 orderQuoteAmt -= filledVol;
             }
         }
@@ -587,6 +1243,8 @@ contract Pair is IPair {
             bidOrders[id].amount = uint96(orderQuoteAmt);
             bidOrders[id].revAmount = uint96(orderBaseAmt);
         }
+        checkFillCooldown(order.gridId, id);
+        countOrderFill(id);
 
         return (amt, filledVol - totalFee);
     }
@@ -597,9 +1255,12 @@ contract Pair is IPair {
         uint256 amt,
         uint256 maxAmt,
         uint256 minAmt // base amount
-    ) public {
+    ) public lock {
         if (maxAmt > 0) require(maxAmt >= amt);
         if (minAmt > 0) require(minAmt <= amt);
+        if (isAskGridOrder(id)) {
+            checkReverseEnabled(askOrders[id].gridId, true);
+        }
 
         (uint256 filledAmt, uint256 filledVol) = fillBidOrder(msg.sender, id, amt);
 
@@ -608,15 +1269,11 @@ contract Pair is IPair {
         }
         if (filledVol > 0) {
             // transfer quote token to taker
-            quoteToken.transfer(msg.sender, filledVol);
+            settle(quoteToken, filledVol, 0);
             // transfer base token from taker
-
-            IERC20Minimal(Currency.unwrap(baseToken)).safeTransferFrom(
-                msg.sender,
-                address(this),
-                filledAmt
-            );
+            settle(baseToken, 0, filledAmt);
         }
+        autoHarvest(orderGridId(id));
     }
 
     // taker is sell, amtList, maxAmt, minAmt is base token amount
@@ -625,7 +1282,7 @@ contract Pair is IPair {
         uint96[] calldata amtList,
         uint256 maxAmt,
         uint256 minAmt // base amount
-    ) public {
+    ) public lock {
         if (idList.length != amtList.length) {
             revert InvalidParam();
         }
@@ -661,15 +1318,235 @@ contract Pair is IPair {
         }
         if (filledVol > 0) {
             // transfer quote token to taker
-            quoteToken.transfer(msg.sender, filledVol);
+            settle(quoteToken, filledVol, 0);
             // transfer base token from taker
+            settle(baseToken, 0, filledAmt);
+        }
+        autoHarvestOrders(idList);
+    }
 
-            IERC20Minimal(Currency.unwrap(baseToken)).safeTransferFrom(
-                msg.sender,
-                address(this),
-                filledAmt
+    // fill ask orders, revert if the price of any order is out of [minPrice, maxPrice],
+    // 0 means no bound
+    function fillAskOrdersInRange(
+        uint64[] calldata idList,
+        uint256[] calldata amtList,
+        uint256 maxAmt,
+        uint256 minAmt,
+        uint256 minPrice,
+        uint256 maxPrice
+    ) public {
+        checkPriceRange(idList, true, minPrice, maxPrice);
+        fillAskOrders(idList, amtList, maxAmt, minAmt);
+    }
+
+    // fill bid orders, revert if the price of any order is out of [minPrice, maxPrice],
+    // 0 means no bound
+    function fillBidOrdersInRange(
+        uint64[] calldata idList,
+        uint96[] calldata amtList,
+        uint256 maxAmt,
+        uint256 minAmt,
+        uint256 minPrice,
+        uint256 maxPrice
+    ) public {
+        checkPriceRange(idList, false, minPrice, maxPrice);
+        fillBidOrders(idList, amtList, maxAmt, minAmt);
+    }
+
+    // taker buys at the ask order price or the bid order reverse price, sells at
+    // the bid order price or the ask order reverse price
+    function checkPriceRange(
+        uint64[] calldata idList,
+        bool buy,
+        uint256 minPrice,
+        uint256 maxPrice
+    ) private view {
+        for (uint i = 0; i < idList.length; ++i) {
+            bool isAsk = isAskGridOrder(idList[i]);
+            Order storage order = isAsk
+                ? askOrders[idList[i]]
+                : bidOrders[idList[i]];
+            // empty or canceled orders, and disabled reverse orders, are skipped by
+            // the fill loops too
+            if (
+                (isAsk == buy ? order.amount : order.revAmount) == 0 ||
+                (isAsk != buy && !reverseEnabled(order.gridId, isAsk))
+            ) {
+                continue;
+            }
+            uint256 price = isAsk == buy ? order.price : order.revPrice;
+            if (price < minPrice || (maxPrice > 0 && price > maxPrice)) {
+                revert PriceOutOfRange();
+            }
+        }
+    }
+
+    // quote filling ask orders against current orders state without executing,
+    // returns filled base amount, quote volume less the keeper tip and trading fee of every order,
+    // see quoteOrders for where the quote may differ from the fill
+    function quoteAskOrders(
+        uint64[] calldata idList,
+        uint256[] calldata amtList
+    )
+        public
+        view
+        returns (
+            uint256[] memory baseAmts,
+            uint256[] memory quoteVols,
+            uint256[] memory fees
+        )
+    {
+        return quoteOrders(idList, amtList, true);
+    }
+
+    // quote filling bid orders against current orders state without executing,
+    // returns filled base amount, quote volume and trading fee of every order,
+    // see quoteOrders for where the quote may differ from the fill
+    function quoteBidOrders(
+        uint64[] calldata idList,
+        uint256[] calldata amtList
+    )
+        public
+        view
+        returns (
+            uint256[] memory baseAmts,
+            uint256[] memory quoteVols,
+            uint256[] memory fees
+        )
+    {
+        return quoteOrders(idList, amtList, false);
+    }
+
+    // every order is quoted on its own from the current state, so the quotes may differ
+    // from a real fill: an id listed twice is quoted twice from the same amounts, the
+    // fill cooldown is not checked, and blockSelfFill applies to msg.sender, so call it
+    // from the taker's address; the fee tier is picked by each order's volume as in the fill
+    function quoteOrders(
+        uint64[] calldata idList,
+        uint256[] calldata amtList,
+        bool takerBuy
+    )
+        private
+        view
+        returns (
+            uint256[] memory baseAmts,
+            uint256[] memory quoteVols,
+            uint256[] memory fees
+        )
+    {
+        if (idList.length != amtList.length) {
+            revert InvalidParam();
+        }
+
+        baseAmts = new uint256[](idList.length);
+        quoteVols = new uint256[](idList.length);
+        fees = new uint256[](idList.length);
+        for (uint i = 0; i < idList.length; ++i) {
+            (uint256 amt, uint256 vol, bool makerFee) = takerBuy
+                ? quoteAskOrder(idList[i], amtList[i])
+                : quoteBidOrder(idList[i], amtList[i]);
+            uint256 fee = (tradingFee(vol) * vol + 999999) / 1000000;
+            if (makerFee) {
+                vol = takerBuy ? vol - fee : vol + fee;
+            }
+            if (takerBuy) {
+                vol -= quoteKeeperTip(idList[i], vol);
+            }
+            baseAmts[i] = amt;
+            quoteVols[i] = vol;
+            fees[i] = fee;
+        }
+    }
+
+    // keeper tip of filling the sell order with vol, same as the spread profit in fillAskOrder
+    function quoteKeeperTip(
+        uint64 id,
+        uint256 vol
+    ) private view returns (uint256) {
+        Order memory order = getGridOrder(id);
+        GridConfig storage conf = gridConfigs[order.gridId];
+        if (conf.compound || conf.keeperTipBps == 0) {
+            return 0;
+        }
+        bool isAsk = isAskGridOrder(id);
+        uint256 quota = reverseQuota(
+            order.gridId,
+            isAsk ? order.revPrice : order.price
+        );
+        uint256 orderQuoteAmt = isAsk ? order.revAmount : order.amount;
+        if (orderQuoteAmt < quota) {
+            vol = orderQuoteAmt + vol > quota ? orderQuoteAmt + vol - quota : 0;
+        }
+        return (vol * conf.keeperTipBps) / 10000;
+    }
+
+    // same as fillAskOrder, but no state changed
+    function quoteAskOrder(
+        uint64 id,
+        uint256 amt
+    ) private view returns (uint256, uint256, bool) {
+        Order memory order = getGridOrder(id);
+        uint256 orderBaseAmt;
+        uint256 sellPrice;
+
+        if (isAskGridOrder(id)) {
+            orderBaseAmt = order.amount;
+            sellPrice = order.price;
+        } else {
+            orderBaseAmt = order.revAmount;
+            sellPrice = order.revPrice;
+        }
+        if (
+            orderBaseAmt == 0 ||
+            (!isAskGridOrder(id) && !reverseEnabled(order.gridId, false))
+        ) {
+            return (0, 0, false);
+        }
+        amt = checkGridFillable(order.gridId, msg.sender, amt);
+        if (amt > orderBaseAmt) {
+            amt = orderBaseAmt;
+        }
+        return (
+            amt,
+            calcQuoteAmountUp(amt, sellPrice),
+            gridConfigs[order.gridId].makerFee
+        );
+    }
+
+    // same as fillBidOrder, but no state changed
+    function quoteBidOrder(
+        uint64 id,
+        uint256 amt
+    ) private view returns (uint256, uint256, bool) {
+        Order memory order = getGridOrder(id);
+        uint256 orderQuoteAmt;
+        uint256 buyPrice;
+
+        if (isAskGridOrder(id)) {
+            orderQuoteAmt = order.revAmount;
+            buyPrice = order.revPrice;
+        } else {
+            orderQuoteAmt = order.amount;
+            buyPrice = order.price;
+        }
+        if (
+            orderQuoteAmt == 0 ||
+            (isAskGridOrder(id) && !reverseEnabled(order.gridId, true))
+        ) {
+            return (0, 0, false);
+        }
+        amt = checkGridFillable(order.gridId, msg.sender, amt);
+        bool makerFee = gridConfigs[order.gridId].makerFee;
+        uint256 vol = calcQuoteAmount(amt, buyPrice);
+        orderQuoteAmt = fillableQuote(order.gridId, orderQuoteAmt);
+        if (vol > orderQuoteAmt) {
+            return (
+                calcBaseAmountUp(orderQuoteAmt, buyPrice),
+                orderQuoteAmt,
+                makerFee
             );
         }
+        return (amt, vol, makerFee);
     }
 
     function getGridOrder(uint64 id) public view returns (Order memory order) {
@@ -696,82 +1573,559 @@ contract Pair is IPair {
         return orderList;
     }
 
+    // orders with amount or reverse amount in [startId, startId + count),
+    // the ids of a grid's orders are in GridOrderCreated; at most MAX_QUERY_ORDERS
+    // ids are scanned, and the scan stops at the max uint64 id
+    function getActiveOrders(
+        uint64 startId,
+        uint64 count
+    ) public view returns (Order[] memory orderList) {
+        if (count > MAX_QUERY_ORDERS) {
+            count = uint64(MAX_QUERY_ORDERS);
+        }
+        uint256 endId = uint256(startId) + count;
+        if (endId > uint256(type(uint64).max) + 1) {
+            endId = uint256(type(uint64).max) + 1;
+        }
+
+        uint256 active = 0;
+        for (uint256 id = startId; id < endId; id++) {
+            Order memory order = getGridOrder(uint64(id));
+            if (order.amount > 0 || order.revAmount > 0) {
+                active++;
+            }
+        }
+
+        orderList = new Order[](active);
+        active = 0;
+        for (uint256 id = startId; id < endId; id++) {
+            Order memory order = getGridOrder(uint64(id));
+            if (order.amount > 0 || order.revAmount > 0) {
+                orderList[active++] = order;
+            }
+        }
+    }
+
+    // liquidity of the orders in idList, including reverse orders:
+    // askBase can be bought for askQuote, bidQuote can be got for bidBase, fees excluded;
+    // orders of paused or not started grids and disabled reverse orders can not be filled
+    // and are not counted
+    function getDepth(
+        uint64[] calldata idList
+    )
+        public
+        view
+        returns (
+            uint256 askBase,
+            uint256 askQuote,
+            uint256 bidBase,
+            uint256 bidQuote
+        )
+    {
+        for (uint i = 0; i < idList.length; ++i) {
+            Order memory order = getGridOrder(idList[i]);
+            GridConfig storage conf = gridConfigs[order.gridId];
+            if (conf.paused || block.timestamp < conf.startTime) {
+                continue;
+            }
+            bool isAsk = isAskGridOrder(idList[i]);
+            if (!reverseEnabled(order.gridId, isAsk)) {
+                order.revAmount = 0;
+            }
+            if (!isAsk) {
+                // sell orders are the reverse orders of buy orders
+                (order.amount, order.revAmount) = (order.revAmount, order.amount);
+                (order.price, order.revPrice) = (order.revPrice, order.price);
+            }
+            askBase += order.amount;
+            askQuote += (uint256(order.amount) * order.price) / PRICE_MULTIPLIER;
+            bidQuote += order.revAmount;
+            if (order.revPrice > 0) {
+                bidBase +=
+                    (uint256(order.revAmount) * PRICE_MULTIPLIER) /
+                    order.revPrice;
+            }
+        }
+    }
+
+    function getGridConfig(
+        uint64 gridId
+    ) public view returns (GridConfig memory) {
+        return gridConfigs[gridId];
+    }
+
+    function getOwnerGrids(
+        address owner
+    ) public view returns (uint64[] memory) {
+        return ownerGrids[owner];
+    }
+
     function getGridProfits(uint64 gridId) public view returns (uint256) {
         return gridConfigs[gridId].profits;
     }
 
-    function sweepGridProfits(uint64 gridId, uint256 amt, address to) public {
-        GridConfig memory conf = gridConfigs[gridId];
+    function getGridFees(uint64 gridId) public view returns (uint256) {
+        return gridConfigs[gridId].fees;
+    }
+
+    // profits and fees sweepGridProfits can take now, 0 before they are settled
+    function getSweepableProfits(uint64 gridId) public view returns (uint256) {
+        GridConfig storage conf = gridConfigs[gridId];
+        return uint256(conf.profits) + conf.fees - unsettledProfits(conf);
+    }
+
+    // paused grid can still be canceled and its profits can still be sweeped
+    function setGridPaused(uint64 gridId, bool paused) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.paused = paused;
+        emit SetGridPaused(gridId, paused);
+    }
+
+    // only stops the owner address itself, not other wallets of the owner
+    function setGridBlockSelfFill(uint64 gridId, bool blocked) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.blockSelfFill = blocked;
+        emit SetGridBlockSelfFill(gridId, blocked);
+    }
+
+    // only affects non-compound grids
+    function setGridCeilQuota(uint64 gridId, bool ceilQuota) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.ceilQuota = ceilQuota;
+        emit SetGridCeilQuota(gridId, ceilQuota);
+    }
+
+    // set compound of the sell side, see setGridBidCompound for the buy side
+    // when switching to non-compound, the quote above the reverse quota of the orders
+    // in idList is moved to grid profits, idList should contain all orders of the grid
+    function setGridCompound(
+        uint64 gridId,
+        bool compound,
+        uint64[] calldata idList
+    ) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.compound = compound;
+        if (!compound) {
+            uint256 profits = 0;
+            for (uint i = 0; i < idList.length; ++i) {
+                profits += flushOrderQuota(gridId, idList[i]);
+            }
+            if (profits > 0) {
+                addGridProfits(gridId, profits);
+            }
+        }
+        emit SetGridCompound(gridId, compound);
+    }
+
+    // set compound of the buy side, which starts as the grid's compound and is not changed
+    // by setGridCompound; there is no
+    // reverse quota to flush on the buy side, the lp fees of later fills go to the grid fees
+    function setGridBidCompound(uint64 gridId, bool compound) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.bidCompound = compound;
+        emit SetGridBidCompound(gridId, compound);
+    }
+
+    // max quote amount of a non-compound grid order at buyPrice, quote above it is profits
+    function reverseQuota(
+        uint64 gridId,
+        uint256 buyPrice
+    ) private view returns (uint256) {
+        GridConfig storage conf = gridConfigs[gridId];
+        uint256 base = conf.quotaBase == 0 ? conf.baseAmt : conf.quotaBase;
+        if (conf.amountInQuote) {
+            return base;
+        }
+        return
+            conf.ceilQuota
+                ? calcQuoteAmountUp(base, buyPrice)
+                : calcQuoteAmount(base, buyPrice);
+    }
+
+    // cap the quote amount of the order to the reverse quota, returns the quote above the quota
+    function flushOrderQuota(
+        uint64 gridId,
+        uint64 id
+    ) private returns (uint256 excess) {
+        bool isAsk = isAskGridOrder(id);
+        Order storage order = isAsk ? askOrders[id] : bidOrders[id];
+        if (order.gridId != gridId) {
+            revert NotGridOrder();
+        }
+
+        uint256 quota = reverseQuota(
+            gridId,
+            isAsk ? order.revPrice : order.price
+        );
+        uint256 quoteAmt = isAsk ? order.revAmount : order.amount;
+        if (quoteAmt <= quota) {
+            return 0;
+        }
+
+        excess = quoteAmt - quota;
+        if (isAsk) {
+            order.revAmount = uint96(quota);
+        } else {
+            order.amount = uint96(quota);
+        }
+    }
+
+    // filled tokens of the side which does not reverse are kept in the orders until canceled
+    function setGridReverse(
+        uint64 gridId,
+        bool askNoReverse,
+        bool bidNoReverse
+    ) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.askNoReverse = askNoReverse;
+        conf.bidNoReverse = bidNoReverse;
+        emit SetGridReverse(gridId, askNoReverse, bidNoReverse);
+    }
+
+    // fills exceeding the max fill amount are partially filled, not reverted
+    function setGridMaxFillAmt(uint64 gridId, uint96 maxFillAmt) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.maxFillAmt = maxFillAmt;
+        emit SetGridMaxFillAmt(gridId, maxFillAmt);
+    }
+
+    // the taker of a fill pays less by the tip, which is paid from the grid's spread profit
+    function setGridKeeperTip(uint64 gridId, uint16 bps) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+        if (bps > 10000) {
+            revert InvalidParam();
+        }
+
+        conf.keeperTipBps = bps;
+        emit SetGridKeeperTip(gridId, bps);
+    }
+
+    // an order of the grid can be filled again blocks after its latest fill
+    function setGridFillCooldown(uint64 gridId, uint16 blocks) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.fillCooldown = blocks;
+        emit SetGridFillCooldown(gridId, blocks);
+    }
+
+    // change how much quote the non-compound grid keeps for reverse orders, existing
+    // orders are not touched; amount is in quote if amountInQuote, 0 resets to baseAmt
+    function setGridReverseQuotaBase(uint64 gridId, uint96 amount) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.quotaBase = amount;
+        emit SetGridReverseQuotaBase(gridId, amount);
+    }
+
+    // fills of the grid send its profits and fees to the owner once they reach threshold,
+    // only while the pair's profitSettlementDelay is 0
+    function setGridAutoHarvest(uint64 gridId, uint128 threshold) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+
+        conf.harvestThreshold = threshold;
+        emit SetGridAutoHarvest(gridId, threshold);
+    }
+
+    // bps of every profits payout of the grid goes to beneficiary, the rest to the owner
+    function setGridBeneficiary(
+        uint64 gridId,
+        address beneficiary,
+        uint16 bps
+    ) public {
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+        if (bps > 10000 || (bps > 0 && beneficiary == address(0))) {
+            revert InvalidParam();
+        }
+
+        conf.beneficiary = beneficiary;
+        conf.beneficiaryBps = bps;
+        emit SetGridBeneficiary(gridId, beneficiary, bps);
+    }
+
+    // sweep profits and fees of the grid, fees first
+    // amt 0 means sweep all settled profits, amounts within the settlement delay are kept;
+    // revert if profits less than minAmt
+    function sweepGridProfits(
+        uint64 gridId,
+        uint256 amt,
+        uint256 minAmt,
+        address to
+    ) public lock {
+        GridConfig storage conf = gridConfigs[gridId];
+        // grid not created, or canceled with nothing left to sweep
+        if (conf.owner == address(0)) {
+            revert InvalidGridId();
+        }
         require(conf.owner == msg.sender);
 
-        if (conf.profits > amt) {
-            amt = conf.profits;
+        uint256 fees = conf.fees;
+        uint256 profits = conf.profits + fees;
+        if (profits < minAmt) {
+            revert NoProfits();
+        }
+        uint256 settled = profits - unsettledProfits(conf);
+        if (settled < minAmt || (settled == 0 && profits > 0)) {
+            revert ProfitsNotSettled();
+        }
+        if (amt == 0 || amt > settled) {
+            amt = settled;
         }
         if (amt == 0) {
             return;
         }
 
-        gridConfigs[gridId].profits = conf.profits - uint128(amt);
-        IERC20Minimal(Currency.unwrap(quoteToken)).safeTransferFrom(
-            msg.sender,
-            to,
-            amt
-        );
+        unchecked {
+            if (amt > fees) {
+                conf.fees = 0;
+                conf.profits = uint128(profits - amt);
+            } else {
+                conf.fees = uint128(fees - amt);
+            }
+        }
+        payProfits(conf, amt, to);
+        // all orders of the grid are cancelled
+        if (conf.orders == 0 && amt == profits) {
+            delete gridConfigs[gridId];
+        }
     }
 
     // cancel grid order will cancel both ask order and bid order
-    function cancelGridOrders(uint64[] calldata idList) public {
-        uint256 baseAmt = 0;
-        uint256 quoteAmt = 0;
+    function cancelGridOrders(uint64[] calldata idList) public lock {
+        (uint256 totalBaseAmt, uint256 totalQuoteAmt) = cancelGridOrderList(
+            idList
+        );
+
+        settle(baseToken, totalBaseAmt, 0);
+        settle(quoteToken, totalQuoteAmt, 0);
+    }
+
+    // cancel bps/10000 of every order of the grid, the grid orders keep active unless
+    // bps is 10000; idList must contain all orders of the grid once, as the grid baseAmt
+    // is reduced too
+    function cancelGridPercent(
+        uint64 gridId,
+        uint64[] calldata idList,
+        uint256 bps
+    ) public lock {
+        if (bps > 10000) {
+            revert InvalidParam();
+        }
+        GridConfig storage conf = gridConfigs[gridId];
+        if (conf.owner != msg.sender) {
+            revert NotGridOwner();
+        }
+        checkGridOrderSet(gridId, conf.orders, idList);
+
         uint256 totalBaseAmt = 0;
         uint256 totalQuoteAmt = 0;
+        if (bps == 10000) {
+            // all cut, cancel the grid
+            (totalBaseAmt, totalQuoteAmt) = cancelGridOrderList(idList);
+        } else {
+            for (uint i = 0; i < idList.length; ++i) {
+                (uint256 baseAmt, uint256 quoteAmt) = reduceGridOrder(
+                    idList[i],
+                    bps
+                );
+                totalBaseAmt += baseAmt;
+                totalQuoteAmt += quoteAmt;
+            }
+            conf.baseAmt -= uint96((uint256(conf.baseAmt) * bps) / 10000);
+            conf.quotaBase -= uint96((uint256(conf.quotaBase) * bps) / 10000);
+        }
 
-        for (uint i = 0; i < idList.length; ) {
-            uint64 id = idList[i];
-            Order memory order;
-            bool isAsk = isAskGridOrder(id);
+        emit CancelGridPercent(gridId, bps, totalBaseAmt, totalQuoteAmt);
+        settle(baseToken, totalBaseAmt, 0);
+        settle(quoteToken, totalQuoteAmt, 0);
+    }
 
-            if (isAsk) {
-                order = askOrders[id];
-                baseAmt = order.amount;
-                quoteAmt = order.revAmount;
-            } else {
-                order = bidOrders[id];
-                baseAmt = order.revAmount;
-                quoteAmt = order.amount;
-            }
-            uint64 gridId = order.gridId;
-            GridConfig memory conf = gridConfigs[gridId];
-            if (msg.sender != conf.owner) {
+    // revert if idList is not every order of the grid exactly once, the ids of each side
+    // must be ascending
+    function checkGridOrderSet(
+        uint64 gridId,
+        uint32 orders,
+        uint64[] calldata idList
+    ) private view {
+        if (idList.length != orders) {
+            revert InvalidParam();
+        }
+        uint64 lastAskId = 0;
+        uint64 lastBidId = 0;
+        for (uint i = 0; i < idList.length; ++i) {
+            uint64 id = idList[i];
+            if (getGridOrder(id).gridId != gridId) {
                 revert NotGridOrder();
             }
-
-            emit CancelGridOrder(msg.sender, id, gridId, baseAmt, quoteAmt);
-
-            if (isAsk) {
-                delete askOrders[id];
+            if (isAskGridOrder(id)) {
+                if (id <= lastAskId) {
+                    revert InvalidParam();
+                }
+                lastAskId = id;
             } else {
-                delete bidOrders[id];
+                if (id <= lastBidId) {
+                    revert InvalidParam();
+                }
+                lastBidId = id;
             }
+        }
+    }
+
+    // reduce both amount and reverse amount of the order by bps/10000, returns the reduced amounts
+    function reduceGridOrder(
+        uint64 id,
+        uint256 bps
+    ) private returns (uint256 baseAmt, uint256 quoteAmt) {
+        Order storage order = isAskGridOrder(id) ? askOrders[id] : bidOrders[id];
+        uint256 amt = (uint256(order.amount) * bps) / 10000;
+        uint256 revAmt = (uint256(order.revAmount) * bps) / 10000;
+        order.amount -= uint96(amt);
+        order.revAmount -= uint96(revAmt);
+        if (isAskGridOrder(id)) {
+            (baseAmt, quoteAmt) = (amt, revAmt);
+        } else {
+            (baseAmt, quoteAmt) = (revAmt, amt);
+        }
+    }
+
+    function cancelGridOrderList(
+        uint64[] calldata idList
+    ) private returns (uint256 totalBaseAmt, uint256 totalQuoteAmt) {
+        for (uint i = 0; i < idList.length; ) {
+            (uint256 baseAmt, uint256 quoteAmt) = cancelGridOrder(idList[i]);
 
             unchecked {
                 ++i;
-                --conf.orders;
                 totalBaseAmt += baseAmt;
                 totalQuoteAmt += quoteAmt;
             }
-            if (conf.orders == 0) {
+        }
+    }
+
+    function cancelGridOrder(
+        uint64 id
+    ) private returns (uint256 baseAmt, uint256 quoteAmt) {
+        Order memory order;
+        bool isAsk = isAskGridOrder(id);
+
+        if (isAsk) {
+            order = askOrders[id];
+            baseAmt = order.amount;
+            quoteAmt = order.revAmount;
+        } else {
+            order = bidOrders[id];
+            baseAmt = order.revAmount;
+            quoteAmt = order.amount;
+        }
+        uint64 gridId = order.gridId;
+        GridConfig storage conf = gridConfigs[gridId];
+        if (msg.sender != conf.owner) {
+            revert NotGridOrder();
+        }
+
+        emit CancelGridOrder(msg.sender, id, gridId, baseAmt, quoteAmt);
+
+        if (isAsk) {
+            delete askOrders[id];
+        } else {
+            delete bidOrders[id];
+        }
+
+        unchecked {
+            --conf.orders;
+        }
+        if (conf.orders == 0) {
+            removeOwnerGrid(conf.owner, conf.ownerIndex);
+            --activeGrids;
+            // the config of unswept profits and fees is kept for sweepGridProfits,
+            // and deleted once they are swept
+            if (conf.profits == 0 && conf.fees == 0) {
                 delete gridConfigs[gridId];
             }
         }
-        if (baseAmt > 0) {
-            // transfer
-            baseToken.transfer(msg.sender, totalBaseAmt);
+    }
+
+    // move the unswept profits and fees of the cancelled grids to gridId, the unsettled
+    // part waits for the settlement delay again
+    function carryGridProfits(uint64[] memory gridIds, uint64 gridId) private {
+        GridConfig storage conf = gridConfigs[gridId];
+        for (uint i = 0; i < gridIds.length; ++i) {
+            GridConfig storage old = gridConfigs[gridIds[i]];
+            // config already deleted or grid still has orders
+            if (old.owner != msg.sender || old.orders > 0) {
+                continue;
+            }
+            conf.profits += old.profits;
+            conf.fees += old.fees;
+            addPendingProfits(conf, unsettledProfits(old));
+            delete gridConfigs[gridIds[i]];
+        }
+    }
+
+    // append the grid to owner's grids, returns the index of the grid
+    function addOwnerGrid(
+        address owner,
+        uint64 gridId
+    ) private returns (uint32) {
+        uint64[] storage grids = ownerGrids[owner];
+        if (grids.length >= MAX_OWNER_GRIDS) {
+            revert ExceedMaxOwnerGrids();
         }
-        if (quoteAmt > 0) {
-            // transfer
-            quoteToken.transfer(msg.sender, totalQuoteAmt);
+        grids.push(gridId);
+        return uint32(grids.length - 1);
+    }
+
+    // remove the grid at index by moving the last grid to it
+    function removeOwnerGrid(address owner, uint32 index) private {
+        uint64[] storage grids = ownerGrids[owner];
+        uint64 lastGridId = grids[grids.length - 1];
+        if (lastGridId != grids[index]) {
+            grids[index] = lastGridId;
+            gridConfigs[lastGridId].ownerIndex = index;
         }
+        grids.pop();
     }
 
     /// @inheritdoc IPair
@@ -784,11 +2138,123 @@ contract Pair is IPair {
         emit SetFeeProtocol(feeProtocolOld, _feeProtocol);
     }
 
+    /// @inheritdoc IPair
+    function setMaxActiveGrids(uint32 _maxActiveGrids) external override {
+        require(msg.sender == IFactory(factory).owner());
+
+        uint32 maxActiveGridsOld = maxActiveGrids;
+        maxActiveGrids = _maxActiveGrids;
+        emit SetMaxActiveGrids(maxActiveGridsOld, _maxActiveGrids);
+    }
+
+    /// @inheritdoc IPair
+    function setMaxOrdersPerSide(uint16 _maxOrdersPerSide) external override {
+        require(msg.sender == IFactory(factory).owner());
+
+        uint16 maxOrdersPerSideOld = maxOrdersPerSide;
+        maxOrdersPerSide = _maxOrdersPerSide;
+        emit SetMaxOrdersPerSide(maxOrdersPerSideOld, _maxOrdersPerSide);
+    }
+
+    /// @inheritdoc IPair
+    function setMinPriceGap(uint128 _minPriceGap) external override {
+        require(msg.sender == IFactory(factory).owner());
+
+        uint128 minPriceGapOld = minPriceGap;
+        minPriceGap = _minPriceGap;
+        emit SetMinPriceGap(minPriceGapOld, _minPriceGap);
+    }
+
+    /// @inheritdoc IPair
+    function setProfitSettlementDelay(uint32 delay) external override {
+        require(msg.sender == IFactory(factory).owner());
+
+        uint32 delayOld = profitSettlementDelay;
+        profitSettlementDelay = delay;
+        emit SetProfitSettlementDelay(delayOld, delay);
+    }
+
+    /// @inheritdoc IPair
+    function setFeeTiers(
+        uint128[3] calldata minVols,
+        uint24[3] calldata fees
+    ) external override {
+        require(msg.sender == IFactory(factory).owner());
+
+        for (uint i = 0; i < 3; ++i) {
+            // enabled tiers must be ascending and come first
+            if (i > 0 && minVols[i] > 0 && minVols[i] <= minVols[i - 1]) {
+                revert InvalidParam();
+            }
+            if (i > 0 && minVols[i] > 0 && minVols[i - 1] == 0) {
+                revert InvalidParam();
+            }
+            // stored fee is always the effective fee
+            if (fees[i] > slot0.fee) {
+                revert InvalidParam();
+            }
+            feeTiers[i] = FeeTier({minVol: minVols[i], fee: fees[i]});
+        }
+        emit SetFeeTiers(minVols, fees);
+    }
+
+    /// @inheritdoc IPair
+    function solvency()
+        external
+        view
+        override
+        returns (int256 baseSurplus, int256 quoteSurplus)
+    {
+        baseSurplus = int256(baseToken.balanceOfSelf()) - int256(baseReserve);
+        quoteSurplus =
+            int256(quoteToken.balanceOfSelf()) -
+            int256(quoteReserve);
+    }
+
+    /// @inheritdoc IPair
+    function rescueExcess(
+        address recipient
+    ) external override lock returns (uint256 baseAmt, uint256 quoteAmt) {
+        require(msg.sender == IFactory(factory).owner());
+
+        // tokens transferred to the pair directly are not recorded in reserves
+        uint256 balance = baseToken.balanceOfSelf();
+        if (balance > baseReserve) {
+            baseAmt = balance - baseReserve;
+            baseToken.transfer(recipient, baseAmt);
+        }
+        balance = quoteToken.balanceOfSelf();
+        if (balance > quoteReserve) {
+            quoteAmt = balance - quoteReserve;
+            quoteToken.transfer(recipient, quoteAmt);
+        }
+
+        emit RescueExcess(msg.sender, recipient, baseAmt, quoteAmt);
+    }
+
+    /// @inheritdoc IPair
+    function rescueToken(
+        Currency token,
+        address recipient
+    ) external override lock returns (uint256 amount) {
+        require(msg.sender == IFactory(factory).owner());
+        if (token == baseToken || token == quoteToken) {
+            revert PairToken();
+        }
+
+        amount = token.balanceOfSelf();
+        if (amount > 0) {
+            token.transfer(recipient, amount);
+        }
+
+        emit RescueToken(msg.sender, recipient, Currency.unwrap(token), amount);
+    }
+
     /// @inheritdoc IPair
     function collectProtocol(
         address recipient,
         uint256 amount
-    ) external override returns (uint256) {
+    ) external override lock returns (uint256) {
         require(msg.sender == IFactory(factory).owner());
 
         amount = amount > protocolFees ? protocolFees : amount;
@@ -796,6 +2262,7 @@ contract Pair is IPair {
         if (amount > 0) {
             if (amount == protocolFees) amount--; // ensure that the slot is not cleared, for gas savings
             protocolFees -= amount;
+            quoteReserve -= amount;
             quoteToken.transfer(recipient, amount);
 
             emit CollectProtocol(msg.sender, recipient, amount);
@@ -10,9 +10,15 @@ interface IPair is IPairEvents {
     /// @notice Thrown when param invalid
     error InvalidParam();
 
+    /// @notice Thrown when the pair is reentered
+    error Locked();
+
     /// @notice Thrown when grid buy price0 or sell price0 invalid
     error InvalidGridPrice();
 
+    /// @notice Thrown when the lowest sell price is not higher than the highest buy price
+    error CrossedGrid();
+
     /// @notice Thrown when grid quote amount invalid
     error InvalidGridAmount();
 
@@ -25,6 +31,12 @@ interface IPair is IPairEvents {
     /// @notice Thrown when buy price less than 0 or sell prive overflow
     error InvalidGapPrice();
 
+    /// @notice Thrown when grid order base amount at sell order reverse price is 0 quote amount
+    error PriceTooLowForOrderSize();
+
+    /// @notice Thrown when adjacent grid orders have the same quote amount
+    error DegenerateLadder();
+
     /// @notice Thrown when base token not enough
     error NotEnoughBaseToken();
 
@@ -65,6 +77,54 @@ interface IPair is IPairEvents {
     /// @notice Thrown when gridId invalid
     error InvalidGridId();
 
+    /// @notice Thrown when msg.sender is NOT grid owner
+    error NotGridOwner();
+
+    /// @notice Thrown when fill a paused grid
+    error GridPaused();
+
+    /// @notice Thrown when fill a reverse order whose side does not reverse
+    error ReverseDisabled();
+
+    /// @notice Thrown when fill a grid before its start time
+    error GridNotStarted();
+
+    /// @notice Thrown when grid owner fills a grid which blocks self fill
+    error SelfFill();
+
+    /// @notice Thrown when the price of an order to fill is out of the taker's price range
+    error PriceOutOfRange();
+
+    /// @notice Thrown when an order is filled again within the grid's fill cooldown
+    error FillCooldown();
+
+    /// @notice Thrown when grid profits is less than the minimal amount to sweep
+    error NoProfits();
+
+    /// @notice Thrown when sweeping grid profits and less than minAmt, or none, is settled
+    error ProfitsNotSettled();
+
+    /// @notice Thrown when active grids reach maxActiveGrids
+    error ExceedMaxActiveGrids();
+
+    /// @notice Thrown when the sell or buy orders of a grid exceed maxOrdersPerSide
+    error ExceedMaxOrdersPerSide();
+
+    /// @notice Thrown when the price gap between adjacent orders of a new grid is below minPriceGap
+    error PriceGapTooSmall();
+
+    /// @notice Thrown when the owner's grids reach MAX_OWNER_GRIDS
+    error ExceedMaxOwnerGrids();
+
+    /// @notice Thrown when placing a grid for an owner who does not allow the caller
+    error NotGridFunder();
+
+    /// @notice Thrown when the pair receives less than the deposit, e.g. a fee-on-transfer token
+    error DepositShortfall();
+
+    /// @notice Thrown when rescuing the base or quote token of the pair
+    error PairToken();
+
     //////////////////////////////// Immutables ////////////////////////////////
 
     /// @notice The contract that deployed the pair, which must adhere to the IUniswapV3Factory interface
@@ -105,9 +165,88 @@ interface IPair is IPairEvents {
     /// @dev Protocol fees will never exceed uint256 max, all fee is quote token
     function protocolFees() external view returns (uint256 quote);
 
+    /// @notice The amount of base token recorded for the pair's orders
+    function baseReserve() external view returns (uint256);
+
+    /// @notice The amount of quote token recorded for the pair's orders, grid profits and protocol fees
+    function quoteReserve() external view returns (uint256);
+
+    /// @notice The max count of active grids of the pair, 0 means no limit
+    function maxActiveGrids() external view returns (uint32);
+
+    /// @notice The count of grids which have orders not canceled
+    function activeGrids() external view returns (uint32);
+
+    /// @notice The seconds new grid profits can not be swept, 0 means no delay. An amount waits between
+    /// one and two delays, later fills can not extend it
+    function profitSettlementDelay() external view returns (uint32);
+
+    /// @notice The max count of sell orders and of buy orders of a new grid, 0 means no limit
+    function maxOrdersPerSide() external view returns (uint16);
+
+    /// @notice The min price gap between adjacent orders of a new grid, 0 means no limit
+    function minPriceGap() external view returns (uint128);
+
     /// @notice Set pair protocol fee
     function setFeeProtocol(uint8 _feeProtocol) external;
 
+    /// @notice Set the max count of active grids of the pair
+    /// @dev Must be called by the factory owner. Lower than current active grids only blocks new grids
+    /// @param _maxActiveGrids The max count of active grids, 0 means no limit
+    function setMaxActiveGrids(uint32 _maxActiveGrids) external;
+
+    /// @notice Set the max count of sell orders and of buy orders of a new grid
+    /// @dev Must be called by the factory owner. Placed grids are not affected
+    /// @param _maxOrdersPerSide The max count of orders per side, 0 means no limit
+    function setMaxOrdersPerSide(uint16 _maxOrdersPerSide) external;
+
+    /// @notice Set the min price gap between adjacent orders of a new grid
+    /// @dev Must be called by the factory owner. Placed grids are not affected
+    /// @param _minPriceGap The min price gap, 0 means no limit
+    function setMinPriceGap(uint128 _minPriceGap) external;
+
+    /// @notice Set the delay before grid profits can be swept
+    /// @dev Must be called by the factory owner. Grid auto harvest does nothing while the delay is not 0
+    /// @param delay The seconds new profits wait before they can be swept, 0 means no delay
+    function setProfitSettlementDelay(uint32 delay) external;
+
+    /// @notice Set the trading fee tiers by fill quote volume
+    /// @dev Must be called by the factory owner. A fill pays the fee of the highest tier whose minVol is reached
+    /// @param minVols The min quote volume of every tier, ascending, 0 disables the tier, the enabled tiers must come first
+    /// @param fees The trading fee of every tier, in hundredths of a bip, i.e. 1e-6, not higher than the pair fee
+    function setFeeTiers(
+        uint128[3] calldata minVols,
+        uint24[3] calldata fees
+    ) external;
+
+    /// @notice The token balances of the pair minus its reserves, for monitoring
+    /// @dev A negative value means the pair holds less than it owes, e.g. a fee-on-transfer or rebasing token
+    /// @return baseSurplus The base token balance minus baseReserve
+    /// @return quoteSurplus The quote token balance minus quoteReserve
+    function solvency()
+        external
+        view
+        returns (int256 baseSurplus, int256 quoteSurplus);
+
+    /// @notice Transfer the tokens sent to the pair directly, which exceed the pair's reserves
+    /// @dev Must be called by the factory owner. Orders, profits and protocol fees are not touched
+    /// @param recipient The address to which the excess tokens should be sent
+    /// @return baseAmt The excess base token amount
+    /// @return quoteAmt The excess quote token amount
+    function rescueExcess(
+        address recipient
+    ) external returns (uint256 baseAmt, uint256 quoteAmt);
+
+    /// @notice Transfer all of a token which is neither base nor quote token of the pair
+    /// @dev Must be called by the factory owner
+    /// @param token The token sent to the pair by mistake
+    /// @param recipient The address to which the token should be sent
+    /// @return amount The amount of token rescued
+    function rescueToken(
+        Currency token,
+        address recipient
+    ) external returns (uint256 amount);
+
     /// @notice Collect the protocol fee accrued to the pair
     /// @param recipient The address to which collected protocol fees should be sent
     /// @param amount The maximum amount
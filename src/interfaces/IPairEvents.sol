@@ -31,6 +31,12 @@ interface IPairEvents {
         bool compound
     );
 
+    /// @notice Emitted when a grid is created, with the tokens locked by the grid orders
+    /// @param gridId The grid id
+    /// @param baseAmt The base amount of all sell orders
+    /// @param quoteAmt The quote amount of all buy orders
+    event GridDeposit(uint64 indexed gridId, uint256 baseAmt, uint256 quoteAmt);
+
     /// @notice Emitted when a grid order was canceled
     /// @param owner The owner of the canceled order
     /// @param orderId The orderId of the order to be canceled
@@ -45,6 +51,13 @@ interface IPairEvents {
         uint256 quoteAmt
     );
 
+    /// @notice Emitted when a part of every order of a grid was canceled
+    /// @param gridId The grid id
+    /// @param bps The canceled part of every order, in 1/10000
+    /// @param baseAmt The base token refunded
+    /// @param quoteAmt The quote token refunded
+    event CancelGridPercent(uint64 indexed gridId, uint256 bps, uint256 baseAmt, uint256 quoteAmt);
+
     /// @notice Emitted when a grid order was filled
     /// @param orderId The orderId of the order to be canceled
     /// @param sidePrice The grid order side: ASK=1 BID=2
@@ -67,14 +80,131 @@ interface IPairEvents {
         address taker
     );
 
+    /// @notice Emitted when a grid with a label is created, fills of the grid orders can be attributed by the
+    /// order ids of GridOrderCreated
+    /// @param gridId The grid id
+    /// @param label The client defined tag of the grid
+    event GridLabel(uint64 indexed gridId, bytes32 indexed label);
+
+    /// @notice Emitted when a grid was paused or unpaused by the grid owner
+    /// @param gridId The grid id
+    /// @param paused If the grid is paused
+    event SetGridPaused(uint64 indexed gridId, bool paused);
+
+    /// @notice Emitted when the grid owner blocks or allows filling the grid from the owner address
+    /// @param gridId The grid id
+    /// @param blocked If the grid owner can not fill the grid
+    event SetGridBlockSelfFill(uint64 indexed gridId, bool blocked);
+
+    /// @notice Emitted when the grid owner disables or enables the reverse orders of each side
+    /// @param gridId The grid id
+    /// @param askNoReverse If filled sell orders are not bought back
+    /// @param bidNoReverse If filled buy orders are not sold back
+    event SetGridReverse(uint64 indexed gridId, bool askNoReverse, bool bidNoReverse);
+
+    /// @notice Emitted when the grid owner switches the sell side of the grid to compound or not
+    /// @param gridId The grid id
+    /// @param compound If the sell side is compound
+    event SetGridCompound(uint64 indexed gridId, bool compound);
+
+    /// @notice Emitted when the grid owner switches the buy side of the grid to compound or not
+    /// @param gridId The grid id
+    /// @param compound If the buy side is compound
+    event SetGridBidCompound(uint64 indexed gridId, bool compound);
+
+    /// @notice Emitted when the grid owner changes the rounding of the reverse quota
+    /// @param gridId The grid id
+    /// @param ceilQuota If the reverse quota is rounded up
+    event SetGridCeilQuota(uint64 indexed gridId, bool ceilQuota);
+
+    /// @notice Emitted when the grid owner changes the max base amount of one order filled by one fill
+    /// @param gridId The grid id
+    /// @param maxFillAmt The max base amount, 0 means no limit
+    event SetGridMaxFillAmt(uint64 indexed gridId, uint96 maxFillAmt);
+
+    /// @notice Emitted when the grid owner changes the keeper tip paid from the spread profit
+    /// @param gridId The grid id
+    /// @param bps The tip in basis points of the spread profit
+    event SetGridKeeperTip(uint64 indexed gridId, uint16 bps);
+
+    /// @notice Emitted when the grid owner changes the min blocks between two fills of the same order
+    /// @param gridId The grid id
+    /// @param blocks The min blocks, 0 means no limit
+    event SetGridFillCooldown(uint64 indexed gridId, uint16 blocks);
+
+    /// @notice Emitted when the grid owner changes the reverse quota reference of the grid
+    /// @param gridId The grid id
+    /// @param amount The base amount the reverse quota is computed from, 0 means the grid baseAmt
+    event SetGridReverseQuotaBase(uint64 indexed gridId, uint96 amount);
+
+    /// @notice Emitted when an owner allows or disallows an address to place grids on its behalf
+    /// @param owner The owner of the grids
+    /// @param funder The address placing and funding the grids
+    /// @param allowed If the funder is allowed
+    event SetGridFunder(address indexed owner, address indexed funder, bool allowed);
+
+    /// @notice Emitted when the grid owner changes who shares the grid's profits
+    /// @param gridId The grid id
+    /// @param beneficiary The address receiving the share
+    /// @param bps The share of every profits payout, in basis points
+    event SetGridBeneficiary(
+        uint64 indexed gridId,
+        address beneficiary,
+        uint16 bps
+    );
+
+    /// @notice Emitted when the grid owner changes the threshold of sending profits to the owner on fills
+    /// @param gridId The grid id
+    /// @param threshold The quote amount of profits and fees, 0 means disabled
+    event SetGridAutoHarvest(uint64 indexed gridId, uint128 threshold);
+
     /// @notice Emitted by a pair when fee protocol changed
     /// @param feeProtocolOld The gridId of the order to be canceled
     /// @param feeProtocol The orderId of the order to be canceled
     event SetFeeProtocol(uint8 feeProtocolOld, uint8 feeProtocol);
 
+    /// @notice Emitted by a pair when profit settlement delay changed
+    /// @param delayOld The previous delay in seconds
+    /// @param delay The new delay in seconds
+    event SetProfitSettlementDelay(uint32 delayOld, uint32 delay);
+
+    /// @notice Emitted by a pair when trading fee tiers changed
+    /// @param minVols The min quote volume of every tier
+    /// @param fees The trading fee of every tier
+    event SetFeeTiers(uint128[3] minVols, uint24[3] fees);
+
+    /// @notice Emitted by a pair when max active grids changed
+    /// @param maxActiveGridsOld The previous max active grids
+    /// @param maxActiveGrids The new max active grids
+    event SetMaxActiveGrids(uint32 maxActiveGridsOld, uint32 maxActiveGrids);
+
+    /// @notice Emitted by a pair when max orders per side changed
+    /// @param maxOrdersPerSideOld The previous max orders per side
+    /// @param maxOrdersPerSide The new max orders per side
+    event SetMaxOrdersPerSide(uint16 maxOrdersPerSideOld, uint16 maxOrdersPerSide);
+
+    /// @notice Emitted by a pair when min price gap changed
+    /// @param minPriceGapOld The previous min price gap
+    /// @param minPriceGap The new min price gap
+    event SetMinPriceGap(uint128 minPriceGapOld, uint128 minPriceGap);
+
     /// @notice Emitted when the collected protocol fees are withdrawn by the factory owner
     /// @param sender The address that collects the protocol fees
     /// @param recipient The address that receives the collected protocol fees
     /// @param amount The amount of quote protocol fees that is withdrawn
     event CollectProtocol(address indexed sender, address indexed recipient, uint256 amount);
+
+    /// @notice Emitted when the tokens sent to the pair directly are rescued by the factory owner
+    /// @param sender The address that rescues the tokens
+    /// @param recipient The address that receives the rescued tokens
+    /// @param baseAmt The amount of base token rescued
+    /// @param quoteAmt The amount of quote token rescued
+    event RescueExcess(address indexed sender, address indexed recipient, uint256 baseAmt, uint256 quoteAmt);
+
+    /// @notice Emitted when a foreign token held by the pair is rescued by the factory owner
+    /// @param sender The address that rescues the token
+    /// @param recipient The address that receives the rescued token
+    /// @param token The rescued token
+    /// @param amount The amount of token rescued
+    event RescueToken(address indexed sender, address indexed recipient, address token, uint256 amount);
 }
@@ -37,17 +37,22 @@ library CurrencyLibrary {
     Currency public constant NATIVE = Currency.wrap(address(0));
 
     function transfer(Currency currency, address to, uint256 amount) internal {
+        if (!currency.tryTransfer(to, amount)) {
+            if (currency.isNative()) revert NativeTransferFailed();
+            revert ERC20TransferFailed();
+        }
+    }
+
+    /// @dev Same as transfer, but returns false instead of reverting when the transfer fails
+    function tryTransfer(Currency currency, address to, uint256 amount) internal returns (bool success) {
         // implementation from
         // https://github.com/transmissions11/solmate/blob/e8f96f25d48fe702117ce76c79228ca4f20206cb/src/utils/SafeTransferLib.sol
 
-        bool success;
         if (currency.isNative()) {
             assembly {
                 // Transfer the ETH and store if it succeeded or not.
                 success := call(gas(), to, amount, 0, 0, 0, 0)
             }
-
-            if (!success) revert NativeTransferFailed();
         } else {
             assembly {
                 // We'll write our calldata to this slot below, but restore it later.
@@ -72,8 +77,6 @@ library CurrencyLibrary {
                 mstore(0x60, 0) // Restore the zero slot to zero.
                 mstore(0x40, memPointer) // Restore the memPointer.
             }
-
-            if (!success) revert ERC20TransferFailed();
         }
     }
 
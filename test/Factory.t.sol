@@ -69,6 +69,10 @@ contract FactoryTest is Test {
         // fee rate not exist
         vm.expectRevert();
         factory.createPair(address(sea), address(usdc), 3000);
+
+        // identical tokens, base and quote reserves would share one balance
+        vm.expectRevert();
+        factory.createPair(address(usdc), address(usdc), 500);
     }
 
     function test_setQuoteToken_failsNoauth() public {
@@ -4,15 +4,20 @@ pragma solidity ^0.8.13;
 import {IPair} from "../src/interfaces/IPair.sol";
 import {IPairEvents} from "../src/interfaces/IPairEvents.sol";
 
-import {Test, console} from "forge-std/Test.sol";
+import {Test, Vm, console, StdStorage, stdStorage} from "forge-std/Test.sol";
 import {Pair} from "../src/Pair.sol";
 import {Factory} from "../src/Factory.sol";
+import {Currency} from "../src/libraries/Currency.sol";
 
 import {SEA} from "./utils/SEA.sol";
 import {USDC} from "./utils/USDC.sol";
 import {WETH} from "./utils/WETH.sol";
+import {ReentrantToken} from "./utils/ReentrantToken.sol";
+import {FeeOnTransferToken} from "./utils/FeeOnTransferToken.sol";
 
 contract PairTest is Test {
+    using stdStorage for StdStorage;
+
     Pair public pair;
     Factory public factory;
     SEA public sea;
@@ -56,7 +61,11 @@ contract PairTest is Test {
             buyPrice0: buyPrice0,
             sellGap: gap,
             buyGap: gap,
-            compound: false
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
         });
         sea.approve(address(pair), type(uint96).max);
         usdc.approve(address(pair), type(uint96).max);
@@ -89,183 +98,3644 @@ contract PairTest is Test {
         assertEq(usdc.balanceOf(other), usdcAmt - usdcUsed);
     }
 
-    function test_CancelGridOrder() public {
-        // counter.increment();
-        // assertEq(counter.number(), 1);
+    function test_GridDeposit() public {
+        address maker = address(0x111);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 3,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.recordLogs();
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        Vm.Log[] memory logs = vm.getRecordedLogs();
+        bool found = false;
+        for (uint i = 0; i < logs.length; ++i) {
+            if (logs[i].topics[0] != IPairEvents.GridDeposit.selector) {
+                continue;
+            }
+            found = true;
+            assertEq(uint256(logs[i].topics[1]), 1);
+            (uint256 baseAmt, uint256 quoteAmt) = abi.decode(
+                logs[i].data,
+                (uint256, uint256)
+            );
+            assertEq(baseAmt, 2 * perBaseAmt);
+            assertEq(baseAmt, sea.balanceOf(address(pair)));
+            assertEq(quoteAmt, usdc.balanceOf(address(pair)));
+            assertEq(quoteAmt, 10000 * 10 ** 6 - usdc.balanceOf(maker));
+        }
+        assertTrue(found);
     }
 
-    // not compound grid
-    function test_FillAskGridOrder_01() public {
+    function test_PlaceGridOrder_failsCrossedGrid() public {
         address maker = address(0x111);
-        address taker = address(0x333);
-        uint16 asks = 1;
-
         uint256 perBaseAmt = 100 * 10 ** 18;
         uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
-        uint256 usdcAmt = (10 * uint256(asks) * perBaseAmt * sellPrice0) /
-            PRICE_MULTIPLIER;
-
         uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
-        sea.transfer(maker, uint256(asks) * 100 * (10 ** 18));
-        usdc.transfer(taker, usdcAmt);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
 
-        // place order
         vm.startPrank(maker);
+        // highest buy price equals the lowest sell price
         Pair.GridOrderParam memory param = Pair.GridOrderParam({
-            asks: asks,
-            bids: 0,
+            asks: 1,
+            bids: 1,
             baseAmount: uint96(perBaseAmt),
             sellPrice0: sellPrice0,
-            buyPrice0: sellPrice0 - gap,
+            buyPrice0: sellPrice0,
             sellGap: gap,
             buyGap: gap,
-            compound: false
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
         });
         sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.CrossedGrid.selector);
+        pair.placeGridOrders(param);
+
+        param.buyPrice0 = sellPrice0 + gap;
+        vm.expectRevert(IPair.CrossedGrid.selector);
+        pair.placeGridOrders(param);
+
+        // buy price is not used by a sell only grid
+        param.bids = 0;
+        pair.placeGridOrders(param);
+
+        param.bids = 1;
+        param.buyPrice0 = sellPrice0 - gap;
         pair.placeGridOrders(param);
         vm.stopPrank();
+        assertEq(pair.getGridOrder(1).price, sellPrice0 - gap);
+    }
 
-        // fill order
-        vm.startPrank(taker);
+    function test_PlaceGridOrder_amountInQuote() public {
+        address maker = address(0x111);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 perQuoteAmt = 100 * 10 ** 6;
+        uint256 buyPrice0 = (49 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 0,
+            bids: 2,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: buyPrice0 + gap,
+            buyPrice0: buyPrice0,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
         usdc.approve(address(pair), type(uint96).max);
-        uint64 id = 0x8000000000000001; // first ask grid order
-        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        pair.placeGridOrders(param);
+
+        // every buy order spends 100 USDC
+        param.baseAmount = uint96(perQuoteAmt);
+        param.amountInQuote = true;
+        pair.placeGridOrders(param);
+
+        // only buy only grid can use quote amount
+        param.asks = 1;
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.placeGridOrders(param);
         vm.stopPrank();
 
-        Pair.Order memory order = pair.getGridOrder(id);
-        assertEq(order.amount, 0);
-        assertEq(sea.balanceOf(taker), perBaseAmt);
-        assertEq(sea.balanceOf(address(pair)), 0);
+        assertEq(
+            pair.getGridOrder(1).amount,
+            pair.calcQuoteAmount(perBaseAmt, buyPrice0)
+        );
+        assertEq(
+            pair.getGridOrder(2).amount,
+            pair.calcQuoteAmount(perBaseAmt, buyPrice0 - gap)
+        );
+        assertEq(pair.getGridOrder(3).amount, perQuoteAmt);
+        assertEq(pair.getGridOrder(4).amount, perQuoteAmt);
+        assertTrue(pair.getGridConfig(2).amountInQuote);
+        assertEq(
+            usdc.balanceOf(address(pair)),
+            pair.getGridOrder(1).amount +
+                pair.getGridOrder(2).amount +
+                2 *
+                perQuoteAmt
+        );
+    }
 
-        {
-            uint256 filledVol = (perBaseAmt * (sellPrice0)) / PRICE_MULTIPLIER;
-            uint256 fee = (filledVol * 500) / 1000000;
-            uint256 quota = (perBaseAmt * (sellPrice0 - gap)) /
-                PRICE_MULTIPLIER;
-            uint8 feeProtocol = pair.feeProtocol();
-            assertEq(order.revAmount, quota);
-            assertEq(pair.protocolFees(), fee / feeProtocol);
-            assertEq(pair.getGridProfits(1), filledVol - quota  + fee - fee/feeProtocol);
+    function test_PlaceGridOrder_failsZeroReverseQuote() public {
+        // 0.000001 SEA sell at 2 USDC, reverse buy at 0.5 USDC, which is less than 1 unit of USDC
+        uint256 perBaseAmt = 10 ** 12;
+        uint256 sellPrice0 = (20 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (15 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        assertGt(pair.calcQuoteAmount(perBaseAmt, sellPrice0), 0);
+
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        vm.expectRevert(IPair.PriceTooLowForOrderSize.selector);
+        pair.placeGridOrders(param);
+    }
+
+    function test_PlaceGridOrder_failsDegenerateLadder() public {
+        // 0.000001 SEA at 2 USDC and 2.1 USDC, both are 2 units of USDC
+        uint256 perBaseAmt = 10 ** 12;
+        uint256 sellPrice0 = (20 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (1 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        assertEq(
+            pair.calcQuoteAmount(perBaseAmt, sellPrice0),
+            pair.calcQuoteAmount(perBaseAmt, sellPrice0 + gap)
+        );
+
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        vm.expectRevert(IPair.DegenerateLadder.selector);
+        pair.quoteGridRange(param);
+
+        param.asks = 0;
+        param.bids = 2;
+        vm.expectRevert(IPair.DegenerateLadder.selector);
+        pair.quoteGridRange(param);
+
+        // a single order has no adjacent order
+        param.bids = 1;
+        pair.quoteGridRange(param);
+    }
+
+    function test_PlaceGridOrder_failsZeroBuyGap() public {
+        // found by testFuzz_PlaceGridOrder_ladder: two buy orders at one price
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 0,
+            bids: 2,
+            baseAmount: uint96(100 * 10 ** 6),
+            sellPrice0: 1,
+            buyPrice0: (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12),
+            sellGap: 0,
+            buyGap: 0,
+            compound: false,
+            amountInQuote: true,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        vm.expectRevert(IPair.InvalidGapPrice.selector);
+        pair.quoteGridRange(param);
+
+        param.bids = 1;
+        pair.quoteGridRange(param);
+    }
+
+    // a grid is either placed with a strictly monotonic ladder of nonzero prices,
+    // or rejected with a custom error, never a panic
+    function testFuzz_PlaceGridOrder_ladder(
+        Pair.GridOrderParam memory param
+    ) public {
+        param.asks = uint16(bound(param.asks, 0, 20));
+        param.bids = uint16(bound(param.bids, 0, 20));
+        param.sellPrice0 = bound(param.sellPrice0, 0, uint256(type(uint160).max) + 1);
+        param.buyPrice0 = bound(param.buyPrice0, 0, uint256(type(uint160).max) + 1);
+        param.sellGap = bound(param.sellGap, 0, uint256(type(uint160).max) + 1);
+        param.buyGap = bound(param.buyGap, 0, uint256(type(uint160).max) + 1);
+        param.startTime = 0;
+
+        sea.mint(address(this), type(uint96).max);
+        usdc.mint(address(this), 20 * uint256(type(uint96).max));
+        sea.approve(address(pair), type(uint256).max);
+        usdc.approve(address(pair), type(uint256).max);
+
+        uint64 askId = pair.nextAskOrderId();
+        uint64 bidId = pair.nextBidOrderId();
+        try pair.placeGridOrders(param) {
+            uint256 last = 0;
+            for (uint64 i = 0; i < param.asks; i++) {
+                Pair.Order memory order = pair.getGridOrder(askId + i);
+                assertGt(order.amount, 0);
+                assertGt(order.revPrice, 0);
+                assertLe(order.revPrice, order.price);
+                assertGt(order.price, last);
+                last = order.price;
+            }
+            last = type(uint256).max;
+            for (uint64 i = 0; i < param.bids; i++) {
+                Pair.Order memory order = pair.getGridOrder(bidId + i);
+                assertGt(order.amount, 0);
+                assertGt(order.price, 0);
+                assertGe(order.revPrice, order.price);
+                assertLt(order.price, last);
+                last = order.price;
+            }
+        } catch (bytes memory reason) {
+            assertGe(reason.length, 4);
+            assertTrue(bytes4(reason) != bytes4(0x4e487b71)); // Panic(uint256)
         }
+    }
 
-        uint256 usdcNow = usdc.balanceOf(taker) + pair.getGridProfits(1) + pair.protocolFees() + order.revAmount;
-        assertEq(usdcAmt, usdcNow);
-        assertEq(usdcAmt, usdc.balanceOf(taker) + usdc.balanceOf(address(pair)));
+    function test_SetFeeProtocol() public {
+        assertEq(pair.feeProtocol(), 6);
+
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetFeeProtocol(6, 10);
+        pair.setFeeProtocol(10);
+        assertEq(pair.feeProtocol(), 10);
+
+        vm.expectRevert();
+        pair.setFeeProtocol(3);
+
+        // only factory owner can set fee protocol
+        vm.prank(address(0x111));
+        vm.expectRevert();
+        pair.setFeeProtocol(0);
+
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetFeeProtocol(10, 0);
+        pair.setFeeProtocol(0);
+        assertEq(pair.feeProtocol(), 0);
     }
 
-    function test_FillAskGridOrder_02() public {
+    function test_MaxActiveGrids() public {
         address maker = address(0x111);
-        address taker = address(0x333);
-        uint16 asks = 1;
-
         uint256 perBaseAmt = 100 * 10 ** 18;
         uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
-        uint256 usdcAmt = (10 * uint256(asks) * perBaseAmt * sellPrice0) /
-            PRICE_MULTIPLIER;
-
         uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
-        sea.transfer(maker, uint256(asks) * 100 * (10 ** 18));
-        usdc.transfer(taker, usdcAmt);
+        sea.transfer(maker, 3 * perBaseAmt);
+
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetMaxActiveGrids(0, 2);
+        pair.setMaxActiveGrids(2);
+
+        // only factory owner can set max active grids
+        vm.prank(maker);
+        vm.expectRevert();
+        pair.setMaxActiveGrids(0);
 
-        // place order
         vm.startPrank(maker);
         Pair.GridOrderParam memory param = Pair.GridOrderParam({
-            asks: asks,
+            asks: 1,
             bids: 0,
             baseAmount: uint96(perBaseAmt),
             sellPrice0: sellPrice0,
             buyPrice0: sellPrice0 - gap,
             sellGap: gap,
             buyGap: gap,
-            compound: true
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
         });
         sea.approve(address(pair), type(uint96).max);
         pair.placeGridOrders(param);
-        vm.stopPrank();
+        pair.placeGridOrders(param);
+        assertEq(pair.activeGrids(), 2);
 
-        // fill order
-        vm.startPrank(taker);
-        usdc.approve(address(pair), type(uint96).max);
-        uint64 id = 0x8000000000000001; // first ask grid order
-        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.expectRevert(IPair.ExceedMaxActiveGrids.selector);
+        pair.placeGridOrders(param);
+
+        // cancel all orders of grid 1
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = 0x8000000000000001;
+        pair.cancelGridOrders(idList);
+        assertEq(pair.activeGrids(), 1);
+
+        pair.placeGridOrders(param);
         vm.stopPrank();
+        assertEq(pair.activeGrids(), 2);
+        assertEq(pair.getGridConfig(3).owner, maker);
+    }
 
-        Pair.Order memory order = pair.getGridOrder(id);
-        assertEq(order.amount, 0);
-        assertEq(sea.balanceOf(taker), perBaseAmt);
-        assertEq(sea.balanceOf(address(pair)), 0);
+    function test_MaxOrdersPerSide() public {
+        address maker = address(0x111);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 3 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
 
-        {
-            uint256 filledVol = (perBaseAmt * (sellPrice0)) / PRICE_MULTIPLIER;
-            uint256 fee = (filledVol * 500) / 1000000;
-            uint8 feeProtocol = pair.feeProtocol();
-            assertEq(order.revAmount, filledVol + fee - fee/feeProtocol);
-            assertEq(pair.protocolFees(), fee / feeProtocol);
-            assertEq(pair.getGridProfits(1), 0);
-        }
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetMaxOrdersPerSide(0, 2);
+        pair.setMaxOrdersPerSide(2);
 
-        uint256 usdcNow = usdc.balanceOf(taker) + pair.getGridProfits(1) + pair.protocolFees() + order.revAmount;
-        assertEq(usdcAmt, usdcNow);
-        assertEq(usdcAmt, usdc.balanceOf(taker) + usdc.balanceOf(address(pair)));
-    }
+        // only factory owner can set max orders per side
+        vm.prank(maker);
+        vm.expectRevert();
+        pair.setMaxOrdersPerSide(0);
 
-    function test_FillAskGridOrder_03() public {
-        // counter.increment();
-        // assertEq(counter.number(), 1);
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 3,
+            bids: 2,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.ExceedMaxOrdersPerSide.selector);
+        pair.placeGridOrders(param);
+
+        param.asks = 2;
+        param.bids = 3;
+        vm.expectRevert(IPair.ExceedMaxOrdersPerSide.selector);
+        pair.placeGridOrders(param);
+
+        param.bids = 2;
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+        assertEq(pair.getGridConfig(1).orders, 4);
+
+        // 0 means no limit
+        pair.setMaxOrdersPerSide(0);
+        param.asks = 1;
+        param.bids = 3;
+        vm.prank(maker);
+        pair.placeGridOrders(param);
+        assertEq(pair.getGridConfig(2).orders, 4);
     }
 
-    function test_FillBidGridOrder_01() public {
+    function test_MinPriceGap() public {
         address maker = address(0x111);
-        address taker = address(0x333);
-        uint16 bids = 1;
-
         uint256 perBaseAmt = 100 * 10 ** 18;
-        uint256 buyPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
-        uint256 usdcAmt = (uint256(bids) * perBaseAmt * buyPrice0) /
-            PRICE_MULTIPLIER;
-
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
         uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
-        sea.transfer(taker, perBaseAmt);
-        usdc.transfer(maker, usdcAmt);
+        sea.transfer(maker, 3 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetMinPriceGap(0, uint128(gap));
+        pair.setMinPriceGap(uint128(gap));
+
+        // only factory owner can set min price gap
+        vm.prank(maker);
+        vm.expectRevert();
+        pair.setMinPriceGap(0);
 
-        // place order
         vm.startPrank(maker);
         Pair.GridOrderParam memory param = Pair.GridOrderParam({
-            asks: 0,
-            bids: bids,
+            asks: 2,
+            bids: 2,
             baseAmount: uint96(perBaseAmt),
-            sellPrice0: buyPrice0 + gap,
-            buyPrice0: buyPrice0,
-            sellGap: gap,
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap - 1,
             buyGap: gap,
-            compound: false
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
         });
+        sea.approve(address(pair), type(uint96).max);
         usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.PriceGapTooSmall.selector);
+        pair.placeGridOrders(param);
+
+        param.sellGap = gap;
+        param.buyGap = gap - 1;
+        vm.expectRevert(IPair.PriceGapTooSmall.selector);
         pair.placeGridOrders(param);
+
+        // a single order has no adjacent order
+        param.bids = 1;
+        pair.placeGridOrders(param);
+        assertEq(pair.getGridConfig(1).orders, 3);
         vm.stopPrank();
+    }
 
-        // fill order
-        vm.startPrank(taker);
+    function test_QuoteGridRange() public {
+        address maker = address(0x111);
+        uint256 gap = 10 ** 46;
+        sea.transfer(maker, 200);
+
+        // the highest sell price is just below uint160 max
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 145,
+            bids: 0,
+            baseAmount: 1,
+            sellPrice0: 2 * gap,
+            buyPrice0: gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        (uint256 lowPrice, uint256 highPrice) = pair.quoteGridRange(param);
+        assertEq(lowPrice, gap);
+        assertEq(highPrice, 146 * gap);
+
+        vm.startPrank(maker);
         sea.approve(address(pair), type(uint96).max);
-        uint64 id = 1; // first ask grid order
-        pair.fillBidOrders(id, perBaseAmt, 0, 0);
+        pair.placeGridOrders(param);
+        assertEq(pair.getGridOrder(0x8000000000000091).price, highPrice);
+
+        // the highest sell price exceeds uint160 max
+        param.asks = 200;
+        vm.expectRevert(IPair.InvalidGapPrice.selector);
+        pair.quoteGridRange(param);
+        vm.expectRevert(IPair.InvalidGapPrice.selector);
+        pair.placeGridOrders(param);
         vm.stopPrank();
 
-        Pair.Order memory order = pair.getGridOrder(id);
-        assertEq(order.amount, 0);
-        assertEq(sea.balanceOf(taker), 0);
-        assertEq(sea.balanceOf(address(pair)), perBaseAmt);
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        param.asks = 2;
+        param.bids = 3;
+        param.baseAmount = uint96(100 * 10 ** 18);
+        param.sellPrice0 = sellPrice0;
+        param.buyPrice0 = sellPrice0 - gap;
+        param.sellGap = gap;
+        param.buyGap = gap;
+        (lowPrice, highPrice) = pair.quoteGridRange(param);
+        assertEq(lowPrice, sellPrice0 - 3 * gap);
+        assertEq(highPrice, sellPrice0 + gap);
 
-        {
-            uint256 filledVol = (perBaseAmt * (buyPrice0)) / PRICE_MULTIPLIER;
-            uint256 fee = (filledVol * uint256(pair.fee())) / 1000000;
-            uint8 feeProtocol = pair.feeProtocol();
-            assertEq(order.revAmount, perBaseAmt);
-            assertEq(pair.protocolFees(), fee / feeProtocol);
-            assertEq(pair.getGridProfits(1), fee - fee/feeProtocol);
-        }
+        // gaps wider than the spread, the reverse prices are the range bounds
+        param.asks = 1;
+        param.bids = 1;
+        param.sellGap = 40 * gap;
+        param.buyGap = 20 * gap;
+        (lowPrice, highPrice) = pair.quoteGridRange(param);
+        assertEq(lowPrice, sellPrice0 - 40 * gap);
+        assertEq(highPrice, sellPrice0 + 19 * gap);
+    }
 
-        uint256 usdcNow = usdc.balanceOf(taker) + pair.getGridProfits(1) + pair.protocolFees() + order.amount;
+    function test_OwnerGrids() public {
+        address maker = address(0x111);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 3 * perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        pair.placeGridOrders(param);
+        pair.placeGridOrders(param);
+
+        uint64[] memory grids = pair.getOwnerGrids(maker);
+        assertEq(grids.length, 3);
+        assertEq(grids[0], 1);
+        assertEq(grids[1], 2);
+        assertEq(grids[2], 3);
+
+        // cancel all orders of grid 1, the last grid takes its place
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = 0x8000000000000001;
+        pair.cancelGridOrders(idList);
+        grids = pair.getOwnerGrids(maker);
+        assertEq(grids.length, 2);
+        assertEq(grids[0], 3);
+        assertEq(grids[1], 2);
+
+        idList[0] = 0x8000000000000003;
+        pair.cancelGridOrders(idList);
+        idList[0] = 0x8000000000000002;
+        pair.cancelGridOrders(idList);
+        vm.stopPrank();
+        assertEq(pair.getOwnerGrids(maker).length, 0);
+    }
+
+    function test_GetDepth() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        uint256 buyPrice0 = sellPrice0 - gap;
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 2,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: buyPrice0,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt / 2, 0, 0);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](4);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        idList[2] = 1;
+        idList[3] = 2;
+        (
+            uint256 askBase,
+            uint256 askQuote,
+            uint256 bidBase,
+            uint256 bidQuote
+        ) = pair.getDepth(idList);
+
+        uint256 revAmt = pair.getGridOrder(idList[0]).revAmount;
+        uint256 quoteAmt0 = pair.calcQuoteAmount(perBaseAmt, buyPrice0);
+        uint256 quoteAmt1 = pair.calcQuoteAmount(perBaseAmt, buyPrice0 - gap);
+        assertGt(revAmt, 0);
+        assertEq(askBase, perBaseAmt / 2 + perBaseAmt);
+        assertEq(
+            askQuote,
+            pair.calcQuoteAmount(perBaseAmt / 2, sellPrice0) +
+                pair.calcQuoteAmount(perBaseAmt, sellPrice0 + gap)
+        );
+        assertEq(bidQuote, revAmt + quoteAmt0 + quoteAmt1);
+        assertEq(
+            bidBase,
+            (revAmt * PRICE_MULTIPLIER) / (sellPrice0 - gap) +
+                (quoteAmt0 * PRICE_MULTIPLIER) / buyPrice0 +
+                (quoteAmt1 * PRICE_MULTIPLIER) / (buyPrice0 - gap)
+        );
+
+        // disabled reverse orders are not counted
+        vm.prank(maker);
+        pair.setGridReverse(1, true, false);
+        (askBase, askQuote, bidBase, bidQuote) = pair.getDepth(idList);
+        assertEq(askBase, perBaseAmt / 2 + perBaseAmt);
+        assertEq(bidQuote, quoteAmt0 + quoteAmt1);
+
+        // nor the orders of a paused grid
+        vm.prank(maker);
+        pair.setGridPaused(1, true);
+        (askBase, askQuote, bidBase, bidQuote) = pair.getDepth(idList);
+        assertEq(askBase + askQuote + bidBase + bidQuote, 0);
+    }
+
+    function test_GridLabel() public {
+        address maker = address(0x111);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: "strategy-a"
+        });
+        sea.approve(address(pair), type(uint96).max);
+        vm.expectEmit(true, true, false, true);
+        emit IPairEvents.GridLabel(1, "strategy-a");
+        pair.placeGridOrders(param);
+
+        param.label = "strategy-b";
+        vm.expectEmit(true, true, false, true);
+        emit IPairEvents.GridLabel(2, "strategy-b");
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        Pair.GridConfig memory conf = pair.getGridConfig(1);
+        assertEq(conf.label, bytes32("strategy-a"));
+        // the unused tail of a partially filled label stays zero
+        assertEq(uint256(conf.label) & type(uint176).max, 0);
+        assertEq(conf.owner, maker);
+        assertEq(conf.baseAmt, perBaseAmt);
+        assertEq(pair.getGridConfig(2).label, bytes32("strategy-b"));
+    }
+
+    function test_CancelGridOrder() public {
+        // counter.increment();
+        // assertEq(counter.number(), 1);
+    }
+
+    function test_RolloverGridOrder_carriesProfits() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        uint256 profits = pair.getGridProfits(1);
+        uint256 fees = pair.getGridFees(1);
+        assertGt(profits, 0);
+        assertGt(fees, 0);
+
+        // roll the grid into a one order grid
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        param.asks = 1;
+        vm.startPrank(maker);
+        pair.rolloverGridOrders(idList, param);
+
+        assertEq(pair.getGridConfig(1).owner, address(0));
+        assertEq(pair.getGridProfits(2), profits);
+        assertEq(pair.getGridFees(2), fees);
+
+        uint256 usdcBefore = usdc.balanceOf(maker);
+        pair.sweepGridProfits(2, 0, 0, maker);
+        vm.stopPrank();
+        assertEq(usdc.balanceOf(maker) - usdcBefore, profits + fees);
+        assertEq(usdc.balanceOf(address(pair)), pair.quoteReserve());
+    }
+
+    function test_RolloverGridOrder() public {
+        address maker = address(0x111);
+        uint16 asks = 2;
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, uint256(asks) * perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: asks,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+
+        // move the grid one gap higher, the refunded base token funds the new
+        // grid directly, so no allowance is needed
+        sea.approve(address(pair), 0);
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        param.sellPrice0 = sellPrice0 + gap;
+        param.buyPrice0 = sellPrice0;
+        pair.rolloverGridOrders(idList, param);
+        vm.stopPrank();
+
+        assertEq(sea.balanceOf(maker), 0);
+        assertEq(sea.balanceOf(address(pair)), uint256(asks) * perBaseAmt);
+
+        // old grid orders are canceled
+        assertEq(pair.getGridOrder(idList[0]).amount, 0);
+        assertEq(pair.getGridOrder(idList[1]).amount, 0);
+
+        Pair.Order memory order = pair.getGridOrder(0x8000000000000003);
+        assertEq(order.gridId, 2);
+        assertEq(order.amount, perBaseAmt);
+        assertEq(order.price, sellPrice0 + gap);
+        assertEq(order.revPrice, sellPrice0);
+    }
+
+    // not compound grid
+    function test_FillAskGridOrder_01() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        uint16 asks = 1;
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 usdcAmt = (10 * uint256(asks) * perBaseAmt * sellPrice0) /
+            PRICE_MULTIPLIER;
+
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, uint256(asks) * 100 * (10 ** 18));
+        usdc.transfer(taker, usdcAmt);
+
+        // place order
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: asks,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        // fill order
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        uint64 id = 0x8000000000000001; // first ask grid order
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        Pair.Order memory order = pair.getGridOrder(id);
+        assertEq(order.amount, 0);
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(sea.balanceOf(address(pair)), 0);
+
+        {
+            uint256 filledVol = (perBaseAmt * (sellPrice0)) / PRICE_MULTIPLIER;
+            uint256 fee = (filledVol * 500) / 1000000;
+            uint256 quota = (perBaseAmt * (sellPrice0 - gap)) /
+                PRICE_MULTIPLIER;
+            uint8 feeProtocol = pair.feeProtocol();
+            assertEq(order.revAmount, quota);
+            assertEq(pair.protocolFees(), fee / feeProtocol);
+            assertEq(pair.getGridProfits(1), filledVol - quota);
+            assertEq(pair.getGridFees(1), fee - fee/feeProtocol);
+        }
+
+        uint256 usdcNow = usdc.balanceOf(taker) + pair.getGridProfits(1) + pair.getGridFees(1) + pair.protocolFees() + order.revAmount;
+        assertEq(usdcAmt, usdcNow);
+        assertEq(usdcAmt, usdc.balanceOf(taker) + usdc.balanceOf(address(pair)));
+    }
+
+    function test_FillAskGridOrder_02() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        uint16 asks = 1;
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 usdcAmt = (10 * uint256(asks) * perBaseAmt * sellPrice0) /
+            PRICE_MULTIPLIER;
+
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, uint256(asks) * 100 * (10 ** 18));
+        usdc.transfer(taker, usdcAmt);
+
+        // place order
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: asks,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: true,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        // fill order
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        uint64 id = 0x8000000000000001; // first ask grid order
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        Pair.Order memory order = pair.getGridOrder(id);
+        assertEq(order.amount, 0);
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(sea.balanceOf(address(pair)), 0);
+
+        {
+            uint256 filledVol = (perBaseAmt * (sellPrice0)) / PRICE_MULTIPLIER;
+            uint256 fee = (filledVol * 500) / 1000000;
+            uint8 feeProtocol = pair.feeProtocol();
+            assertEq(order.revAmount, filledVol + fee - fee/feeProtocol);
+            assertEq(pair.protocolFees(), fee / feeProtocol);
+            assertEq(pair.getGridProfits(1), 0);
+        }
+
+        uint256 usdcNow = usdc.balanceOf(taker) + pair.getGridProfits(1) + pair.protocolFees() + order.revAmount;
+        assertEq(usdcAmt, usdcNow);
+        assertEq(usdcAmt, usdc.balanceOf(taker) + usdc.balanceOf(address(pair)));
+    }
+
+    function test_PauseGrid() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        uint16 asks = 2;
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, uint256(asks) * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: asks,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridPaused(1, true);
+        pair.setGridPaused(1, true);
+        vm.stopPrank();
+
+        // only grid owner can pause/unpause grid
+        vm.startPrank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridPaused(1, false);
+
+        uint64 id = 0x8000000000000001;
+        usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.GridPaused.selector);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        // paused grid can still be canceled
+        vm.startPrank(maker);
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = id + 1;
+        pair.cancelGridOrders(idList);
+        assertEq(sea.balanceOf(maker), perBaseAmt);
+
+        pair.setGridPaused(1, false);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(pair.getGridOrder(id).amount, 0);
+    }
+
+    function test_BlockSelfFill() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridBlockSelfFill(1, true);
+        pair.setGridBlockSelfFill(1, true);
+
+        uint64 id = 0x8000000000000001;
+        vm.expectRevert(IPair.SelfFill.selector);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        // only grid owner can block self fill
+        vm.startPrank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridBlockSelfFill(1, false);
+
+        // other takers can still fill the grid
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt / 2, 0, 0);
+        vm.stopPrank();
+        assertEq(sea.balanceOf(taker), perBaseAmt / 2);
+
+        vm.startPrank(maker);
+        pair.setGridBlockSelfFill(1, false);
+        pair.fillAskOrders(id, perBaseAmt / 2, 0, 0);
+        vm.stopPrank();
+        assertEq(sea.balanceOf(maker), perBaseAmt / 2);
+        assertEq(pair.getGridOrder(id).amount, 0);
+    }
+
+    function test_SweepGridProfits() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        uint256 fees = pair.getGridFees(1);
+        uint256 profits = pair.getGridProfits(1) + fees;
+        assertGt(fees, 0);
+        assertGt(profits, fees);
+
+        // only grid owner can sweep profits
+        vm.prank(taker);
+        vm.expectRevert();
+        pair.sweepGridProfits(1, 0, 0, taker);
+
+        vm.startPrank(maker);
+        vm.expectRevert(IPair.NoProfits.selector);
+        pair.sweepGridProfits(1, 0, profits + 1, maker);
+
+        // fees are swept first
+        pair.sweepGridProfits(1, fees / 2, profits, maker);
+        assertEq(pair.getGridFees(1), fees - fees / 2);
+        assertEq(pair.getGridProfits(1), profits - fees);
+
+        pair.sweepGridProfits(1, profits / 2 - fees / 2, 0, maker);
+        assertEq(usdc.balanceOf(maker), profits / 2);
+        assertEq(pair.getGridFees(1), 0);
+        assertEq(pair.getGridProfits(1), profits - profits / 2);
+
+        // amount 0 sweeps all profits
+        pair.sweepGridProfits(1, 0, 0, maker);
+        vm.stopPrank();
+        assertEq(usdc.balanceOf(maker), profits);
+        assertEq(pair.getGridProfits(1), 0);
+        assertEq(pair.getGridFees(1), 0);
+
+        // grid not created
+        vm.prank(maker);
+        vm.expectRevert(IPair.InvalidGridId.selector);
+        pair.sweepGridProfits(2, 0, 0, maker);
+    }
+
+    // lp fees and profits earned before a full cancel are still swept by the owner
+    function test_SweepGridProfits_afterCancel() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        uint256 fees = pair.getGridFees(1);
+        uint256 profits = pair.getGridProfits(1) + fees;
+        assertGt(fees, 0);
+
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = id;
+        uint256 revAmt = pair.getGridOrder(id).revAmount;
+        vm.startPrank(maker);
+        pair.cancelGridOrders(idList);
+        assertEq(usdc.balanceOf(maker), revAmt);
+        assertEq(pair.activeGrids(), 0);
+        assertEq(pair.getOwnerGrids(maker).length, 0);
+        assertEq(pair.getGridFees(1), fees);
+
+        pair.sweepGridProfits(1, 0, 0, maker);
+        assertEq(usdc.balanceOf(maker), revAmt + profits);
+        assertEq(usdc.balanceOf(address(pair)), pair.protocolFees());
+
+        // the config is deleted once swept
+        vm.expectRevert(IPair.InvalidGridId.selector);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        vm.stopPrank();
+    }
+
+    function test_RescueExcess() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        address recipient = address(0x555);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        // send tokens to the pair directly
+        usdc.transfer(address(pair), 100 * 10 ** 6);
+        vm.stopPrank();
+        sea.transfer(address(pair), 10 ** 18);
+
+        uint256 baseReserve = pair.baseReserve();
+        uint256 quoteReserve = pair.quoteReserve();
+        assertEq(baseReserve, perBaseAmt);
+        assertEq(sea.balanceOf(address(pair)), baseReserve + 10 ** 18);
+        assertEq(usdc.balanceOf(address(pair)), quoteReserve + 100 * 10 ** 6);
+
+        // only factory owner can rescue
+        vm.prank(taker);
+        vm.expectRevert();
+        pair.rescueExcess(taker);
+
+        (int256 baseSurplus, int256 quoteSurplus) = pair.solvency();
+        assertEq(baseSurplus, 10 ** 18);
+        assertEq(quoteSurplus, 100 * 10 ** 6);
+
+        vm.expectEmit(true, true, false, true);
+        emit IPairEvents.RescueExcess(address(this), recipient, 10 ** 18, 100 * 10 ** 6);
+        (uint256 baseAmt, uint256 quoteAmt) = pair.rescueExcess(recipient);
+        assertEq(baseAmt, 10 ** 18);
+        assertEq(quoteAmt, 100 * 10 ** 6);
+        assertEq(sea.balanceOf(recipient), 10 ** 18);
+        assertEq(usdc.balanceOf(recipient), 100 * 10 ** 6);
+        assertEq(pair.baseReserve(), baseReserve);
+        assertEq(pair.quoteReserve(), quoteReserve);
+
+        // orders are not touched
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        uint256 revAmt = pair.getGridOrder(idList[0]).revAmount;
+        uint256 profits = pair.getGridProfits(1) + pair.getGridFees(1);
+        vm.prank(maker);
+        pair.cancelGridOrders(idList);
+        assertEq(sea.balanceOf(maker), perBaseAmt);
+        assertEq(usdc.balanceOf(maker), revAmt);
+        assertEq(pair.baseReserve(), 0);
+        assertEq(pair.quoteReserve(), profits + pair.protocolFees());
+        assertEq(usdc.balanceOf(address(pair)), pair.quoteReserve());
+        (baseSurplus, quoteSurplus) = pair.solvency();
+        assertEq(baseSurplus, 0);
+        assertEq(quoteSurplus, 0);
+
+        // profits of the cancelled grid are still swept by the owner
+        vm.prank(maker);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        assertEq(usdc.balanceOf(maker), revAmt + profits);
+        assertEq(pair.quoteReserve(), pair.protocolFees());
+        assertEq(pair.getGridConfig(1).owner, address(0));
+
+        // the pair holds less quote than it owes
+        deal(address(usdc), address(pair), pair.quoteReserve() - 1);
+        (, quoteSurplus) = pair.solvency();
+        assertEq(quoteSurplus, -1);
+    }
+
+    function test_RescueToken() public {
+        address maker = address(0x111);
+        address recipient = address(0x444);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        // a third token is sent to the pair by mistake
+        WETH weth = new WETH();
+        deal(address(weth), address(pair), 10 ** 18);
+
+        vm.prank(maker);
+        vm.expectRevert();
+        pair.rescueToken(Currency.wrap(address(weth)), recipient);
+
+        vm.expectRevert(IPair.PairToken.selector);
+        pair.rescueToken(Currency.wrap(address(sea)), recipient);
+        vm.expectRevert(IPair.PairToken.selector);
+        pair.rescueToken(Currency.wrap(address(usdc)), recipient);
+
+        vm.expectEmit(true, true, false, true);
+        emit IPairEvents.RescueToken(address(this), recipient, address(weth), 10 ** 18);
+        uint256 amount = pair.rescueToken(Currency.wrap(address(weth)), recipient);
+        assertEq(amount, 10 ** 18);
+        assertEq(weth.balanceOf(recipient), 10 ** 18);
+        assertEq(weth.balanceOf(address(pair)), 0);
+
+        // base and quote are not touched
+        assertEq(sea.balanceOf(address(pair)), perBaseAmt);
+        assertEq(pair.baseReserve(), perBaseAmt);
+    }
+
+    function test_GridMaxFillAmt() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 maxFillAmt = 30 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridMaxFillAmt(1, uint96(maxFillAmt));
+        pair.setGridMaxFillAmt(1, uint96(maxFillAmt));
+        vm.stopPrank();
+
+        // only grid owner can set max fill amount
+        vm.startPrank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridMaxFillAmt(1, 0);
+
+        uint64 id = 0x8000000000000001;
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = id;
+        uint256[] memory amtList = new uint256[](1);
+        amtList[0] = perBaseAmt;
+        (uint256[] memory baseAmts, , ) = pair.quoteAskOrders(idList, amtList);
+        assertEq(baseAmts[0], maxFillAmt);
+
+        // fill the whole order, only max fill amount is filled
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        assertEq(sea.balanceOf(taker), maxFillAmt);
+        assertEq(pair.getGridOrder(id).amount, perBaseAmt - maxFillAmt);
+
+        vm.prank(maker);
+        pair.setGridMaxFillAmt(1, 0);
+        vm.prank(taker);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(pair.getGridOrder(id).amount, 0);
+    }
+
+    function test_ProfitSettlementDelay() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetProfitSettlementDelay(0, 3600);
+        pair.setProfitSettlementDelay(3600);
+
+        // only factory owner can set profit settlement delay
+        vm.prank(taker);
+        vm.expectRevert();
+        pair.setProfitSettlementDelay(0);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        uint256 profits = pair.getGridProfits(1) + pair.getGridFees(1);
+        assertEq(pair.getGridConfig(1).profitTime, block.timestamp);
+        assertEq(pair.getSweepableProfits(1), 0);
+
+        vm.startPrank(maker);
+        vm.expectRevert(IPair.ProfitsNotSettled.selector);
+        pair.sweepGridProfits(1, 0, 0, maker);
+
+        vm.warp(block.timestamp + 3599);
+        assertEq(pair.getSweepableProfits(1), 0);
+        vm.expectRevert(IPair.ProfitsNotSettled.selector);
+        pair.sweepGridProfits(1, 0, 0, maker);
+
+        vm.warp(block.timestamp + 1);
+        assertEq(pair.getSweepableProfits(1), profits);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        vm.stopPrank();
+        assertEq(usdc.balanceOf(maker), profits);
+        assertEq(pair.getSweepableProfits(1), 0);
+    }
+
+    // dust fills right before the delay ends can not keep earlier profits locked
+    function test_ProfitSettlementDelay_dustFills() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        address griefer = address(0x444);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+        usdc.transfer(griefer, 10 ** 6);
+        pair.setProfitSettlementDelay(3600);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint256 t0 = block.timestamp;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        uint256 profits = pair.getGridProfits(1) + pair.getGridFees(1);
+
+        // every dust fill adds 1 lp fee to the grid
+        uint64 id = 0x8000000000000002;
+        vm.startPrank(griefer);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.warp(t0 + 3599);
+        pair.fillAskOrders(id, 10 ** 12, 0, 0);
+        assertEq(pair.getGridFees(1) + pair.getGridProfits(1), profits + 1);
+        vm.warp(t0 + 7198);
+        pair.fillAskOrders(id, 10 ** 12, 0, 0);
+        vm.stopPrank();
+        assertEq(pair.getSweepableProfits(1), 0);
+
+        // the first two amounts are settled one delay after the first dust fill,
+        // the latest dust fill is still pending
+        vm.warp(t0 + 7199);
+        assertEq(pair.getSweepableProfits(1), profits + 1);
+        vm.prank(maker);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        assertEq(usdc.balanceOf(maker), profits + 1);
+        assertEq(pair.getGridFees(1) + pair.getGridProfits(1), 1);
+
+        vm.warp(t0 + 7198 + 3600);
+        assertEq(pair.getSweepableProfits(1), 1);
+    }
+
+    function test_GridStartTime() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        uint256 startTime = block.timestamp + 1 days;
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: uint64(startTime),
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+        assertEq(pair.getGridConfig(1).startTime, startTime);
+
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.GridNotStarted.selector);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        // grid can be canceled before start time
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = id + 1;
+        vm.prank(maker);
+        pair.cancelGridOrders(idList);
+        assertEq(sea.balanceOf(maker), perBaseAmt);
+
+        vm.warp(startTime - 1);
+        vm.prank(taker);
+        vm.expectRevert(IPair.GridNotStarted.selector);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+
+        vm.warp(startTime);
+        vm.prank(taker);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+    }
+
+    function test_GetActiveOrders() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 3 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 3,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+
+        // cancel the middle order
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = 0x8000000000000002;
+        pair.cancelGridOrders(idList);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt / 2, 0, 0);
+        vm.stopPrank();
+
+        Pair.Order[] memory orders = pair.getActiveOrders(0x8000000000000001, 3);
+        assertEq(orders.length, 2);
+        assertEq(orders[0].orderId, 0x8000000000000001);
+        assertEq(orders[0].amount, perBaseAmt / 2);
+        assertGt(orders[0].revAmount, 0);
+        assertEq(orders[0].price, sellPrice0);
+        assertEq(orders[1].orderId, 0x8000000000000003);
+        assertEq(orders[1].amount, perBaseAmt);
+        assertEq(orders[1].price, sellPrice0 + 2 * gap);
+
+        // the scan stops at the max id and is capped in length
+        orders = pair.getActiveOrders(type(uint64).max - 1, 10);
+        assertEq(orders.length, 0);
+        orders = pair.getActiveOrders(0x8000000000000001, type(uint64).max);
+        assertEq(orders.length, 2);
+    }
+
+    function test_CancelGridPercent() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 2,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt / 2, 0, 0);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](4);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        idList[2] = 1;
+        idList[3] = 2;
+        Pair.Order[] memory orders = pair.getGridOrders(idList);
+        uint256 seaBefore = sea.balanceOf(maker);
+        uint256 usdcBefore = usdc.balanceOf(maker);
+
+        vm.startPrank(maker);
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.cancelGridPercent(1, idList, 10001);
+        pair.cancelGridPercent(1, idList, 5000);
+        vm.stopPrank();
+
+        // only grid owner can cancel
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.cancelGridPercent(1, idList, 5000);
+
+        uint256 baseAmt = 0;
+        uint256 quoteAmt = 0;
+        Pair.Order[] memory ordersNow = pair.getGridOrders(idList);
+        for (uint i = 0; i < 4; ++i) {
+            assertEq(ordersNow[i].amount, orders[i].amount - orders[i].amount / 2);
+            assertEq(
+                ordersNow[i].revAmount,
+                orders[i].revAmount - orders[i].revAmount / 2
+            );
+            if (i < 2) {
+                baseAmt += orders[i].amount / 2;
+                quoteAmt += orders[i].revAmount / 2;
+            } else {
+                baseAmt += orders[i].revAmount / 2;
+                quoteAmt += orders[i].amount / 2;
+            }
+        }
+        assertEq(sea.balanceOf(maker) - seaBefore, baseAmt);
+        assertEq(usdc.balanceOf(maker) - usdcBefore, quoteAmt);
+        assertEq(pair.getGridConfig(1).baseAmt, perBaseAmt / 2);
+        assertEq(pair.getGridConfig(1).orders, 4);
+
+        vm.startPrank(maker);
+        // idList must be all orders of the grid, each once
+        uint64[] memory partList = new uint64[](2);
+        partList[0] = 0x8000000000000001;
+        partList[1] = 0x8000000000000002;
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.cancelGridPercent(1, partList, 5000);
+        idList[3] = 1;
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.cancelGridPercent(1, idList, 5000);
+        idList[3] = 2;
+
+        // cutting all cancels the grid
+        pair.cancelGridPercent(1, idList, 10000);
+        vm.stopPrank();
+        assertEq(pair.getGridOrder(0x8000000000000001).amount, 0);
+        assertEq(pair.getGridOrder(2).amount, 0);
+        assertEq(pair.getGridConfig(1).orders, 0);
+        assertEq(pair.getOwnerGrids(maker).length, 0);
+        assertEq(pair.activeGrids(), 0);
+    }
+
+    function test_GridCeilQuota() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        // base * price is not a multiple of PRICE_MULTIPLIER
+        uint256 perBaseAmt = 100 * 10 ** 18 + 1;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        uint256 buyPrice = sellPrice0 - gap;
+        sea.transfer(maker, 2 * perBaseAmt);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        assertEq(
+            pair.calcQuoteAmountUp(perBaseAmt, buyPrice),
+            pair.calcQuoteAmount(perBaseAmt, buyPrice) + 1
+        );
+        assertEq(
+            pair.calcQuoteAmountUp(100 * 10 ** 18, buyPrice),
+            pair.calcQuoteAmount(100 * 10 ** 18, buyPrice)
+        );
+
+        // grid 1 rounds up the reverse quota, grid 2 rounds down
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: buyPrice,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridCeilQuota(1, true);
+        pair.setGridCeilQuota(1, true);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridCeilQuota(1, false);
+
+        uint64 id1 = 0x8000000000000001;
+        uint64 id2 = 0x8000000000000002;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        sea.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id1, perBaseAmt, 0, 0);
+        pair.fillAskOrders(id2, perBaseAmt, 0, 0);
+        assertEq(
+            pair.getGridOrder(id1).revAmount,
+            pair.calcQuoteAmountUp(perBaseAmt, buyPrice)
+        );
+        assertEq(
+            pair.getGridOrder(id2).revAmount,
+            pair.calcQuoteAmount(perBaseAmt, buyPrice)
+        );
+
+        // the reverse orders buy back as much as they can
+        pair.fillBidOrders(id1, perBaseAmt * 2, 0, 0);
+        pair.fillBidOrders(id2, perBaseAmt * 2, 0, 0);
+        vm.stopPrank();
+        assertGe(pair.getGridOrder(id1).amount, perBaseAmt);
+        assertLt(pair.getGridOrder(id2).amount, perBaseAmt);
+    }
+
+    function test_SetGridCompound() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: true,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        // compound grid reverses all quote
+        uint256 revAmt = pair.getGridOrder(id).revAmount;
+        uint256 quota = pair.calcQuoteAmount(perBaseAmt, sellPrice0 - gap);
+        assertGt(revAmt, quota);
+        assertEq(pair.getGridProfits(1), 0);
+
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = id;
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridCompound(1, false, idList);
+
+        vm.prank(maker);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridCompound(1, false);
+        pair.setGridCompound(1, false, idList);
+
+        assertFalse(pair.getGridConfig(1).compound);
+        // the buy side is left to setGridBidCompound
+        assertTrue(pair.getGridConfig(1).bidCompound);
+        assertEq(pair.getGridOrder(id).revAmount, quota);
+        assertEq(pair.getGridProfits(1), revAmt - quota);
+    }
+
+    function test_SetGridBidCompound() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        uint256 buyPrice0 = sellPrice0 - gap;
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 1,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: buyPrice0,
+            sellGap: gap,
+            buyGap: gap,
+            compound: true,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        assertTrue(pair.getGridConfig(1).bidCompound);
+
+        // compound sell side, harvested buy side
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridBidCompound(1, false);
+        pair.setGridBidCompound(1, false);
+        // setGridCompound only switches the sell side
+        pair.setGridCompound(1, true, new uint64[](0));
+        assertTrue(pair.getGridConfig(1).compound);
+        assertFalse(pair.getGridConfig(1).bidCompound);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridBidCompound(1, true);
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        sea.approve(address(pair), type(uint96).max);
+
+        uint64 askId = 0x8000000000000001;
+        pair.fillAskOrders(askId, perBaseAmt, 0, 0);
+        assertGt(
+            pair.getGridOrder(askId).revAmount,
+            pair.calcQuoteAmount(perBaseAmt, sellPrice0 - gap)
+        );
+        assertEq(pair.getGridProfits(1), 0);
+        assertEq(pair.getGridFees(1), 0);
+
+        uint256 bidQuote = pair.getGridOrder(1).amount;
+        pair.fillBidOrders(1, perBaseAmt / 2, 0, 0);
+        uint256 vol = pair.calcQuoteAmount(perBaseAmt / 2, buyPrice0);
+        uint256 fee = (vol * 500 + 999999) / 1000000;
+        assertEq(pair.getGridOrder(1).amount, bidQuote - vol);
+        assertEq(pair.getGridFees(1), fee - fee / 6);
+        vm.stopPrank();
+
+        // the lp fees of the buy side stay in the order again
+        vm.prank(maker);
+        pair.setGridBidCompound(1, true);
+        bidQuote = pair.getGridOrder(1).amount;
+        vm.prank(taker);
+        pair.fillBidOrders(1, perBaseAmt / 4, 0, 0);
+        vol = pair.calcQuoteAmount(perBaseAmt / 4, buyPrice0);
+        uint256 fee2 = (vol * 500 + 999999) / 1000000;
+        assertEq(
+            pair.getGridOrder(1).amount,
+            bidQuote - vol + fee2 - fee2 / 6
+        );
+        assertEq(pair.getGridFees(1), fee - fee / 6);
+    }
+
+    function test_GridReverse() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        // sell orders reverse, buy orders accumulate base token
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 1,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridReverse(1, false, true);
+        pair.setGridReverse(1, false, true);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridReverse(1, false, false);
+
+        uint64 askId = 0x8000000000000001;
+        uint64 bidId = 1;
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+
+        // filled sell order can be bought back
+        pair.fillAskOrders(askId, perBaseAmt, 0, 0);
+        pair.fillBidOrders(askId, perBaseAmt, 0, 0);
+        assertEq(pair.getGridOrder(askId).amount, perBaseAmt);
+
+        // filled buy order can not be sold back
+        pair.fillBidOrders(bidId, perBaseAmt, 0, 0);
+        assertEq(pair.getGridOrder(bidId).revAmount, perBaseAmt);
+        vm.expectRevert(IPair.ReverseDisabled.selector);
+        pair.fillAskOrders(bidId, perBaseAmt, 0, 0);
+
+        // a batch fill skips it and fills the rest
+        uint64[] memory fillList = new uint64[](2);
+        uint256[] memory amtList = new uint256[](2);
+        fillList[0] = bidId;
+        fillList[1] = askId;
+        amtList[0] = perBaseAmt;
+        amtList[1] = perBaseAmt / 2;
+        pair.fillAskOrders(fillList, amtList, 0, 0);
+        assertEq(pair.getGridOrder(bidId).revAmount, perBaseAmt);
+        assertEq(pair.getGridOrder(askId).amount, perBaseAmt / 2);
+        vm.stopPrank();
+
+        // accumulated base token is withdrawn by canceling
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = bidId;
+        vm.prank(maker);
+        pair.cancelGridOrders(idList);
+        assertEq(sea.balanceOf(maker), perBaseAmt);
+    }
+
+    function test_MakerFee() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        uint256 buyPrice = sellPrice0 - gap;
+        sea.transfer(maker, perBaseAmt);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        // maker pays the fee, taker pays or gets the volume exactly
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 1,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: buyPrice,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: true,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](1);
+        uint256[] memory amtList = new uint256[](1);
+        idList[0] = 0x8000000000000001;
+        amtList[0] = perBaseAmt;
+        (
+            ,
+            uint256[] memory quoteVols,
+            uint256[] memory fees
+        ) = pair.quoteAskOrders(idList, amtList);
+
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        uint256 vol = pair.calcQuoteAmount(perBaseAmt, sellPrice0);
+        pair.fillAskOrders(idList[0], perBaseAmt, 0, 0);
+        assertEq(sea.balanceOf(taker), 2 * perBaseAmt);
+        assertEq(usdc.balanceOf(taker), 10000 * 10 ** 6 - vol);
+        assertEq(quoteVols[0] + fees[0], vol);
+
+        // the buy order keeps the fee, taker sells less base
+        uint256 bidQuote = pair.calcQuoteAmount(perBaseAmt, buyPrice);
+        uint256 filledVol = (bidQuote * 1000000) / 1000500;
+        uint256 filled = pair.calcBaseAmountUp(filledVol, buyPrice);
+        idList[0] = 1;
+        (, quoteVols, fees) = pair.quoteBidOrders(idList, amtList);
+        pair.fillBidOrders(idList[0], perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        assertEq(sea.balanceOf(taker), 2 * perBaseAmt - filled);
+        assertEq(usdc.balanceOf(taker), 10000 * 10 ** 6 - vol + filledVol);
+        assertEq(quoteVols[0] - fees[0], filledVol);
+        assertEq(pair.getGridOrder(1).revAmount, filled);
+        assertEq(
+            pair.getGridOrder(1).amount,
+            bidQuote - filledVol - (filledVol * 500 + 999999) / 1000000
+        );
+    }
+
+    // every token held by the pair is owed to an order, a grid's profits or the protocol
+    function checkReserves(uint64[] memory idList, uint64 grids) private view {
+        Pair.Order[] memory orders = pair.getGridOrders(idList);
+        uint256 baseAmt;
+        uint256 quoteAmt = pair.protocolFees();
+        for (uint i = 0; i < orders.length; i++) {
+            if (pair.isAskGridOrder(idList[i])) {
+                baseAmt += orders[i].amount;
+                quoteAmt += orders[i].revAmount;
+            } else {
+                quoteAmt += orders[i].amount;
+                baseAmt += orders[i].revAmount;
+            }
+        }
+        for (uint64 gridId = 1; gridId <= grids; gridId++) {
+            quoteAmt += pair.getGridProfits(gridId) + pair.getGridFees(gridId);
+        }
+        assertEq(pair.baseReserve(), baseAmt);
+        assertEq(pair.quoteReserve(), quoteAmt);
+        (int256 baseSurplus, int256 quoteSurplus) = pair.solvency();
+        assertEq(baseSurplus, 0);
+        assertEq(quoteSurplus, 0);
+    }
+
+    function test_ReservesAfterFills() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18 + 1;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 4 * perBaseAmt);
+        sea.transfer(taker, 4 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        // grid 1 is not compound, grid 2 is compound
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 2,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        param.compound = true;
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](8);
+        for (uint64 i = 0; i < 4; i++) {
+            idList[i] = 0x8000000000000001 + i;
+            idList[4 + i] = 1 + i;
+        }
+        checkReserves(idList, 2);
+
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        for (uint i = 0; i < 2; i++) {
+            uint64 askId = idList[2 * i];
+            uint64 bidId = idList[4 + 2 * i];
+            pair.fillAskOrders(askId, perBaseAmt, 0, 0);
+            checkReserves(idList, 2);
+            pair.fillAskOrders(askId + 1, perBaseAmt / 3, 0, 0);
+            checkReserves(idList, 2);
+            pair.fillBidOrders(askId, perBaseAmt, 0, 0);
+            checkReserves(idList, 2);
+            pair.fillBidOrders(bidId, perBaseAmt / 2, 0, 0);
+            checkReserves(idList, 2);
+            pair.fillAskOrders(bidId, perBaseAmt, 0, 0);
+            checkReserves(idList, 2);
+        }
+        vm.stopPrank();
+    }
+
+    function test_GridKeeperTip() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setGridKeeperTip(1, 10001);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridKeeperTip(1, 2000);
+        pair.setGridKeeperTip(1, 2000);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridKeeperTip(1, 0);
+
+        uint64[] memory idList = new uint64[](1);
+        uint256[] memory amtList = new uint256[](1);
+        idList[0] = 0x8000000000000001;
+        amtList[0] = perBaseAmt;
+        (, uint256[] memory quoteVols, uint256[] memory fees) = pair
+            .quoteAskOrders(idList, amtList);
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        // the quote includes the tip
+        assertEq(
+            usdc.balanceOf(taker),
+            10000 * 10 ** 6 - quoteVols[0] - fees[0]
+        );
+
+        // 20% of the spread profit is left to the taker
+        uint256 vol = pair.calcQuoteAmount(perBaseAmt, sellPrice0);
+        uint256 quota = pair.calcQuoteAmount(perBaseAmt, sellPrice0 - gap);
+        uint256 fee = (vol * 500) / 1000000;
+        uint256 tip = ((vol - quota) * 2000) / 10000;
+        assertEq(usdc.balanceOf(taker), 10000 * 10 ** 6 - vol - fee + tip);
+        assertEq(pair.getGridProfits(1), vol - quota - tip);
+        assertEq(pair.getGridOrder(0x8000000000000001).revAmount, quota);
+    }
+
+    function test_GridAutoHarvest() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 3 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 3,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridAutoHarvest(1, 10 * 10 ** 6);
+        pair.setGridAutoHarvest(1, 10 * 10 ** 6);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridAutoHarvest(1, 0);
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+
+        // about 5.2 USDC is kept in the grid
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        uint256 harvest = pair.getGridProfits(1) + pair.getGridFees(1);
+        assertGt(harvest, 5 * 10 ** 6);
+        assertEq(usdc.balanceOf(maker), 0);
+
+        // about 10.4 USDC is sent to the owner
+        pair.fillAskOrders(0x8000000000000002, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        uint256 vol = pair.calcQuoteAmount(perBaseAmt, sellPrice0 + gap);
+        uint256 fee = (vol * 500 + 999999) / 1000000;
+        harvest += vol - pair.calcQuoteAmount(perBaseAmt, sellPrice0) + fee - fee / 6;
+        assertEq(usdc.balanceOf(maker), harvest);
+        assertEq(pair.getGridProfits(1), 0);
+        assertEq(pair.getGridFees(1), 0);
+        assertEq(usdc.balanceOf(address(pair)), pair.quoteReserve());
+    }
+
+    function test_PlaceGridOrdersBatch() public {
+        address maker = address(0x111);
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 3 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+
+        Pair.GridOrderParam[] memory paramsList = new Pair.GridOrderParam[](2);
+        paramsList[0] = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        paramsList[1] = Pair.GridOrderParam({
+            asks: 1,
+            bids: 2,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0 + gap,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: true,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+
+        vm.startPrank(maker);
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+
+        // an invalid grid reverts the whole batch
+        paramsList[1].sellPrice0 = 0;
+        vm.expectRevert(IPair.InvalidGridPrice.selector);
+        pair.placeGridOrdersBatch(paramsList);
+        paramsList[1].sellPrice0 = sellPrice0 + gap;
+
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.placeGridOrdersBatch(new Pair.GridOrderParam[](0));
+        Pair.GridOrderParam[] memory tooMany = new Pair.GridOrderParam[](
+            pair.MAX_BATCH_GRIDS() + 1
+        );
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.placeGridOrdersBatch(tooMany);
+
+        pair.placeGridOrdersBatch(paramsList);
+        vm.stopPrank();
+
+        assertEq(pair.nextGridId(), 3);
+        assertEq(pair.getGridConfig(1).orders, 2);
+        assertEq(pair.getGridConfig(2).orders, 3);
+        assertTrue(pair.getGridConfig(2).compound);
+        assertEq(pair.getOwnerGrids(maker).length, 2);
+        uint256 quoteAmt = pair.calcQuoteAmount(perBaseAmt, sellPrice0 - gap) +
+            pair.calcQuoteAmount(perBaseAmt, sellPrice0 - 2 * gap);
+        assertEq(sea.balanceOf(maker), 0);
+        assertEq(usdc.balanceOf(maker), 10000 * 10 ** 6 - quoteAmt);
+        assertEq(pair.baseReserve(), 3 * perBaseAmt);
+        assertEq(pair.quoteReserve(), quoteAmt);
+    }
+
+    function test_PlaceGridOrdersFor() public {
+        address owner = address(0x111);
+        address funder = address(0x222);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(funder, 2 * perBaseAmt);
+
+        vm.startPrank(funder);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.placeGridOrdersFor(address(0), param);
+        // the owner must allow the funder first
+        vm.expectRevert(IPair.NotGridFunder.selector);
+        pair.placeGridOrdersFor(owner, param);
+        vm.stopPrank();
+
+        vm.prank(owner);
+        vm.expectEmit(true, true, false, true);
+        emit IPairEvents.SetGridFunder(owner, funder, true);
+        pair.setGridFunder(funder, true);
+        assertTrue(pair.gridFunders(owner, funder));
+
+        vm.startPrank(funder);
+        pair.placeGridOrdersFor(owner, param);
+        assertEq(sea.balanceOf(funder), 0);
+        assertEq(pair.getGridConfig(1).owner, owner);
+        assertEq(pair.getOwnerGrids(owner).length, 1);
+        assertEq(pair.getOwnerGrids(funder).length, 0);
+
+        // the funder has no control of the grid
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        vm.expectRevert(IPair.NotGridOrder.selector);
+        pair.cancelGridOrders(idList);
+        vm.stopPrank();
+
+        vm.prank(owner);
+        pair.cancelGridOrders(idList);
+        assertEq(sea.balanceOf(owner), 2 * perBaseAmt);
+        assertEq(pair.getOwnerGrids(owner).length, 0);
+
+        // a disallowed funder can no longer place grids for the owner
+        vm.prank(owner);
+        pair.setGridFunder(funder, false);
+        vm.prank(funder);
+        vm.expectRevert(IPair.NotGridFunder.selector);
+        pair.placeGridOrdersFor(owner, param);
+    }
+
+    function test_GridReverseQuotaBase() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridReverseQuotaBase(1, uint96(perBaseAmt / 2));
+        pair.setGridReverseQuotaBase(1, uint96(perBaseAmt / 2));
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridReverseQuotaBase(1, 0);
+
+        // the reverse order only keeps quote to buy back half of baseAmt
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        Pair.Order memory order = pair.getGridOrder(id);
+        assertEq(
+            order.revAmount,
+            pair.calcQuoteAmount(perBaseAmt / 2, order.revPrice)
+        );
+        uint256 profits = pair.getGridProfits(1);
+        assertEq(
+            profits,
+            pair.calcQuoteAmountUp(perBaseAmt, sellPrice0) - order.revAmount
+        );
+
+        // a larger quota keeps all the volume for the reverse order
+        vm.prank(maker);
+        pair.setGridReverseQuotaBase(1, uint96(2 * perBaseAmt));
+        vm.prank(taker);
+        pair.fillAskOrders(id + 1, perBaseAmt, 0, 0);
+        order = pair.getGridOrder(id + 1);
+        assertEq(
+            order.revAmount,
+            pair.calcQuoteAmountUp(perBaseAmt, sellPrice0 + gap)
+        );
+        assertEq(pair.getGridProfits(1), profits);
+        assertEq(pair.getGridConfig(1).baseAmt, perBaseAmt);
+    }
+
+    function test_GridBeneficiary() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        address treasury = address(0x444);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setGridBeneficiary(1, treasury, 10001);
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setGridBeneficiary(1, address(0), 2500);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridBeneficiary(1, treasury, 2500);
+        pair.setGridBeneficiary(1, treasury, 2500);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridBeneficiary(1, taker, 10000);
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        uint256 profits = pair.getGridProfits(1) + pair.getGridFees(1);
+        assertGt(profits, 0);
+        vm.prank(maker);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        assertEq(usdc.balanceOf(treasury), (profits * 2500) / 10000);
+        assertEq(usdc.balanceOf(maker) + usdc.balanceOf(treasury), profits);
+
+        // zero share pays everything to the owner
+        vm.prank(maker);
+        pair.setGridBeneficiary(1, treasury, 0);
+        vm.prank(taker);
+        pair.fillAskOrders(0x8000000000000002, perBaseAmt, 0, 0);
+
+        uint256 treasuryBal = usdc.balanceOf(treasury);
+        uint256 makerBal = usdc.balanceOf(maker);
+        profits = pair.getGridProfits(1) + pair.getGridFees(1);
+        vm.prank(maker);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        assertEq(usdc.balanceOf(treasury), treasuryBal);
+        assertEq(usdc.balanceOf(maker), makerBal + profits);
+        assertEq(usdc.balanceOf(address(pair)), pair.quoteReserve());
+    }
+
+    // the first fill reaches the threshold while the pair holds no quote yet
+    function test_GridAutoHarvest_firstFill() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        pair.setGridAutoHarvest(1, 1);
+        vm.stopPrank();
+        assertEq(pair.quoteReserve(), 0);
+
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        uint256 vol = pair.calcQuoteAmountUp(perBaseAmt, sellPrice0);
+        uint256 fee = (vol * 500 + 999999) / 1000000;
+        uint256 revAmt = pair.getGridOrder(id).revAmount;
+        assertEq(usdc.balanceOf(maker), vol - revAmt + fee - fee / 6);
+        assertEq(pair.getGridProfits(1), 0);
+        assertEq(pair.getGridFees(1), 0);
+        assertEq(pair.quoteReserve(), revAmt + pair.protocolFees());
+        assertEq(usdc.balanceOf(address(pair)), pair.quoteReserve());
+    }
+
+    function test_GridAutoHarvest_payoutFails() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        pair.setGridAutoHarvest(1, 1);
+        vm.stopPrank();
+
+        // the quote token refuses to send to the owner, e.g. a blacklist
+        vm.mockCallRevert(
+            address(usdc),
+            abi.encodeWithSelector(usdc.transfer.selector, maker),
+            "blacklisted"
+        );
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+        vm.clearMockedCalls();
+
+        // the fill goes through and the payout stays in the grid
+        uint256 vol = pair.calcQuoteAmountUp(perBaseAmt, sellPrice0);
+        uint256 fee = (vol * 500 + 999999) / 1000000;
+        uint256 revAmt = pair.getGridOrder(id).revAmount;
+        assertEq(usdc.balanceOf(maker), 0);
+        assertEq(pair.getGridProfits(1), vol - revAmt + fee - fee / 6);
+        assertEq(pair.getGridFees(1), 0);
+        assertEq(usdc.balanceOf(address(pair)), pair.quoteReserve());
+
+        vm.prank(maker);
+        pair.sweepGridProfits(1, 0, 0, maker);
+        assertEq(usdc.balanceOf(maker), vol - revAmt + fee - fee / 6);
+    }
+
+    function test_GridFillCooldown() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.expectEmit(true, false, false, true);
+        emit IPairEvents.SetGridFillCooldown(1, 2);
+        pair.setGridFillCooldown(1, 2);
+        vm.stopPrank();
+
+        vm.prank(taker);
+        vm.expectRevert(IPair.NotGridOwner.selector);
+        pair.setGridFillCooldown(1, 0);
+
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+        assertEq(pair.lastFillBlock(id), block.number);
+
+        vm.expectRevert(IPair.FillCooldown.selector);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+        vm.roll(block.number + 1);
+        vm.expectRevert(IPair.FillCooldown.selector);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+
+        // other orders are not affected
+        pair.fillAskOrders(id + 1, perBaseAmt / 4, 0, 0);
+
+        vm.roll(block.number + 1);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+        vm.stopPrank();
+        assertEq(pair.getGridOrder(id).amount, perBaseAmt / 2);
+    }
+
+    function test_OrderFills() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        uint16 asks = 2;
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, uint256(asks) * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: asks,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64 id = 0x8000000000000001;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+        pair.fillAskOrders(id, perBaseAmt / 4, 0, 0);
+        pair.fillAskOrders(id + 1, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        assertEq(pair.orderFills(id), 3);
+        assertEq(pair.orderFills(id + 1), 1);
+
+        // fill counter saturates
+        stdstore
+            .target(address(pair))
+            .sig("orderFills(uint64)")
+            .with_key(uint256(id + 1))
+            .checked_write(type(uint32).max);
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        pair.fillBidOrders(id + 1, perBaseAmt / 2, 0, 0);
+        vm.stopPrank();
+        assertEq(pair.orderFills(id + 1), type(uint32).max);
+
+        // fill counter survives cancel
+        uint64[] memory idList = new uint64[](1);
+        idList[0] = id;
+        vm.prank(maker);
+        pair.cancelGridOrders(idList);
+        assertEq(pair.orderFills(id), 3);
+    }
+
+    // SEA has 18 decimals and USDC has 6 decimals, fill too small base amount
+    // would get 0 quote amount
+    function test_FillGridOrder_failsDust() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 buyPrice0 = (49 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(maker, 1000 * 10 ** 6);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(taker, 1000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 1,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: buyPrice0,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        // 10^11 SEA wei * 4.9 USDC = 0.49 USDC wei
+        uint256 dust = 10 ** 11;
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.ZeroQuoteAmt.selector);
+        pair.fillBidOrders(1, dust, 0, 0);
+
+        // the quote paid by the taker rounds up, 1 USDC wei and 1 USDC wei fee
+        pair.fillAskOrders(0x8000000000000001, dust, 0, 0);
+        vm.stopPrank();
+        assertEq(sea.balanceOf(taker), perBaseAmt + dust);
+        assertEq(usdc.balanceOf(taker), 1000 * 10 ** 6 - 2);
+    }
+
+    // amounts paid by the taker round up, amounts received by the taker round down
+    function test_FillGridOrder_roundsForMaker() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        uint256 buyPrice = sellPrice0 - gap;
+        sea.transfer(maker, perBaseAmt);
+        sea.transfer(taker, 2 * perBaseAmt);
+        usdc.transfer(maker, 1000 * 10 ** 6);
+        usdc.transfer(taker, 1000 * 10 ** 6);
+
+        assertEq(
+            pair.calcBaseAmountUp(1, buyPrice),
+            pair.calcBaseAmount(1, buyPrice) + 1
+        );
+        assertEq(
+            pair.calcBaseAmountUp(495 * 10 ** 6, buyPrice),
+            pair.calcBaseAmount(495 * 10 ** 6, buyPrice)
+        );
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 1,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: buyPrice,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+
+        // buy: quote and fee round up
+        uint256 amt = 10 ** 18 + 1;
+        uint256 vol = pair.calcQuoteAmountUp(amt, sellPrice0);
+        assertEq(vol, pair.calcQuoteAmount(amt, sellPrice0) + 1);
+        pair.fillAskOrders(0x8000000000000001, amt, 0, 0);
+        uint256 usdcBalance = 1000 * 10 ** 6 - vol - (vol * 500 + 999999) / 1000000;
+        assertEq(usdc.balanceOf(taker), usdcBalance);
+
+        // sell more than the buy order can pay for: base paid rounds up, quote got rounds down
+        uint256 bidQuote = pair.getGridOrder(1).amount;
+        uint256 filled = pair.calcBaseAmountUp(bidQuote, buyPrice);
+        pair.fillBidOrders(1, 2 * perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        assertEq(pair.getGridOrder(1).amount, 0);
+        assertEq(pair.getGridOrder(1).revAmount, filled);
+        assertEq(sea.balanceOf(taker), 2 * perBaseAmt + amt - filled);
+        assertEq(
+            usdc.balanceOf(taker),
+            usdcBalance + bidQuote - (bidQuote * 500 + 999999) / 1000000
+        );
+    }
+
+    // the pair receives less base token than the grid orders lock
+    function test_PlaceGridOrder_failsDepositShortfall() public {
+        address maker = address(0x111);
+
+        FeeOnTransferToken token = new FeeOnTransferToken();
+        Pair tokenPair = Pair(
+            payable(factory.createPair(address(token), address(usdc), 500))
+        );
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        token.transfer(maker, 3 * perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        token.approve(address(tokenPair), type(uint96).max);
+        vm.expectRevert(IPair.DepositShortfall.selector);
+        tokenPair.placeGridOrders(param);
+        vm.stopPrank();
+
+        assertEq(token.balanceOf(maker), 3 * perBaseAmt);
+        assertEq(token.balanceOf(address(tokenPair)), 0);
+        assertEq(tokenPair.baseReserve(), 0);
+        assertEq(tokenPair.nextGridId(), 1);
+        assertEq(tokenPair.getGridConfig(1).owner, address(0));
+    }
+
+    // the base token calls back into the pair while it is sent to the taker
+    function test_FillAskGridOrder_reentrancy() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        ReentrantToken token = new ReentrantToken();
+        Pair tokenPair = Pair(
+            payable(factory.createPair(address(token), address(usdc), 500))
+        );
+        (, , bool unlocked) = tokenPair.slot0();
+        assertTrue(unlocked);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        token.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        token.approve(address(tokenPair), type(uint96).max);
+        tokenPair.placeGridOrders(param);
+        vm.stopPrank();
+
+        token.setHook(
+            address(tokenPair),
+            abi.encodeWithSignature(
+                "fillAskOrders(uint64,uint256,uint256,uint256)",
+                0x8000000000000002,
+                perBaseAmt,
+                0,
+                0
+            )
+        );
+        vm.startPrank(taker);
+        usdc.approve(address(tokenPair), type(uint96).max);
+        tokenPair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        // the nested fill is rejected, the outer fill goes through
+        assertFalse(token.success());
+        assertEq(bytes4(token.result()), IPair.Locked.selector);
+        assertEq(token.balanceOf(taker), perBaseAmt);
+        assertEq(tokenPair.getGridOrder(0x8000000000000002).amount, perBaseAmt);
+        (, , unlocked) = tokenPair.slot0();
+        assertTrue(unlocked);
+    }
+
+    // quote volume of the order fits uint96, but not with the trading fee
+    function test_FillGridOrder_failsQuoteWithFeeOverflow() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 10 ** 18;
+        uint256 sellPrice0 = (uint256(type(uint96).max) - 10 ** 24) * 10 ** 12;
+        uint256 gap = sellPrice0 / 10;
+        sea.transfer(maker, perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64 id = 0x8000000000000001;
+        assertLt(
+            pair.calcQuoteAmount(perBaseAmt, sellPrice0),
+            type(uint96).max
+        );
+        vm.prank(taker);
+        vm.expectRevert(IPair.ExceedQuoteAmt.selector);
+        pair.fillAskOrders(id, perBaseAmt, 0, 0);
+        assertEq(pair.getGridOrder(id).amount, perBaseAmt);
+    }
+
+    // protocol fee of a volume close to uint96 max is exact
+    function test_FillGridOrder_largeProtocolFee() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 10 ** 18;
+        uint256 sellPrice0 = 10 ** 40;
+        uint256 gap = sellPrice0 / 10;
+        sea.transfer(maker, perBaseAmt);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint256 vol = pair.calcQuoteAmount(perBaseAmt, sellPrice0);
+        uint256 fee = (vol * 500) / 1000000;
+        deal(address(usdc), taker, vol + fee);
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(0x8000000000000001, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        assertEq(usdc.balanceOf(taker), 0);
+        assertEq(pair.protocolFees(), fee / 6);
+        assertEq(pair.getGridFees(1), fee - fee / 6);
+    }
+
+    function test_QuoteAskOrders() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        // two grids: sell at 5 USDC and 5.5 USDC
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        param.sellPrice0 = sellPrice0 + gap;
+        param.buyPrice0 = sellPrice0;
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        uint256[] memory amtList = new uint256[](2);
+        amtList[0] = perBaseAmt / 2;
+        amtList[1] = perBaseAmt * 2; // more than the order has
+
+        (
+            uint256[] memory baseAmts,
+            uint256[] memory quoteVols,
+            uint256[] memory fees
+        ) = pair.quoteAskOrders(idList, amtList);
+        assertEq(baseAmts[0], perBaseAmt / 2);
+        assertEq(baseAmts[1], perBaseAmt);
+        assertEq(quoteVols[0], pair.calcQuoteAmount(perBaseAmt / 2, sellPrice0));
+        assertEq(quoteVols[1], pair.calcQuoteAmount(perBaseAmt, sellPrice0 + gap));
+        assertEq(fees[0], (quoteVols[0] * 500) / 1000000);
+        assertEq(fees[1], (quoteVols[1] * 500) / 1000000);
+
+        // quote does not change any state
+        assertEq(pair.getGridOrder(idList[0]).amount, perBaseAmt);
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(idList, amtList, 0, 0);
+        vm.stopPrank();
+
+        assertEq(sea.balanceOf(taker), baseAmts[0] + baseAmts[1]);
+        assertEq(
+            usdc.balanceOf(taker),
+            10000 * 10 ** 6 - quoteVols[0] - fees[0] - quoteVols[1] - fees[1]
+        );
+    }
+
+    function test_FeeTiers() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        // 100 USDC: 400, 200 USDC: 300, 400 USDC: 200
+        uint128[3] memory minVols = [uint128(100e6), 200e6, 400e6];
+        uint24[3] memory fees = [uint24(400), 300, 200];
+
+        // only factory owner can set fee tiers
+        vm.prank(taker);
+        vm.expectRevert();
+        pair.setFeeTiers(minVols, fees);
+
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setFeeTiers([uint128(100e6), 100e6, 400e6], fees);
+        // an enabled tier after a disabled one is rejected
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setFeeTiers([uint128(0), 200e6, 400e6], fees);
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setFeeTiers([uint128(100e6), 0, 400e6], fees);
+        // tier fee higher than pair fee 500 is rejected, not capped
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.setFeeTiers(minVols, [uint24(400), 300, 600]);
+
+        vm.expectEmit(false, false, false, true);
+        emit IPairEvents.SetFeeTiers(minVols, fees);
+        pair.setFeeTiers(minVols, fees);
+
+        // 5 USDC per SEA
+        uint64[] memory idList = new uint64[](5);
+        uint256[] memory amtList = new uint256[](5);
+        amtList[0] = 19 * 10 ** 18; // 95 USDC
+        amtList[1] = 20 * 10 ** 18; // 100 USDC
+        amtList[2] = 39 * 10 ** 18; // 195 USDC
+        amtList[3] = 40 * 10 ** 18; // 200 USDC
+        amtList[4] = 80 * 10 ** 18; // 400 USDC
+        uint24[5] memory rates = [uint24(500), 400, 400, 300, 200];
+        for (uint i = 0; i < 5; ++i) {
+            idList[i] = 0x8000000000000001;
+        }
+
+        (, uint256[] memory quoteVols, uint256[] memory quoteFees) = pair
+            .quoteAskOrders(idList, amtList);
+        for (uint i = 0; i < 5; ++i) {
+            assertEq(quoteVols[i], (amtList[i] * 5 * 10 ** 6) / 10 ** 18);
+            assertEq(quoteFees[i], (quoteVols[i] * rates[i]) / 1000000);
+        }
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(idList[3], amtList[3], 0, 0);
+        vm.stopPrank();
+        assertEq(
+            usdc.balanceOf(taker),
+            10000 * 10 ** 6 - quoteVols[3] - quoteFees[3]
+        );
+        assertEq(pair.protocolFees(), quoteFees[3] / pair.feeProtocol());
+    }
+
+    // the same order in one batch is filled serially against the updated order
+    function test_FillAskGridOrders_duplicateOrder() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 1,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: true,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64 id = 0x8000000000000001;
+        uint64[] memory idList = new uint64[](3);
+        idList[0] = id;
+        idList[1] = id;
+        idList[2] = id;
+        uint256[] memory amtList = new uint256[](3);
+        amtList[0] = (perBaseAmt * 3) / 10;
+        amtList[1] = (perBaseAmt * 3) / 10;
+        amtList[2] = perBaseAmt; // only 40% left
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(idList, amtList, 0, 0);
+        vm.stopPrank();
+
+        Pair.Order memory order = pair.getGridOrder(id);
+        uint256 filledVol = pair.calcQuoteAmount(amtList[0], sellPrice0);
+        uint256 fee = (filledVol * 500) / 1000000;
+        uint256 lastVol = pair.calcQuoteAmount((perBaseAmt * 4) / 10, sellPrice0);
+        uint256 lastFee = (lastVol * 500) / 1000000;
+
+        assertEq(order.amount, 0);
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(
+            usdc.balanceOf(taker),
+            10000 * 10 ** 6 - 2 * (filledVol + fee) - lastVol - lastFee
+        );
+        assertEq(pair.orderFills(id), 3);
+    }
+
+    // orders filled by others are skipped, minAmt makes the batch all or nothing
+    function test_FillAskGridOrders_skipEmptyOrder() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        address other = address(0x444);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+        usdc.transfer(other, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        uint256[] memory amtList = new uint256[](2);
+        amtList[0] = perBaseAmt;
+        amtList[1] = perBaseAmt;
+
+        vm.startPrank(other);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(idList[0], perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        vm.expectRevert(IPair.NotEnoughToFill.selector);
+        pair.fillAskOrders(idList, amtList, 0, 2 * perBaseAmt);
+
+        pair.fillAskOrders(idList, amtList, 0, 0);
+        vm.stopPrank();
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(pair.getGridOrder(idList[1]).amount, 0);
+        assertEq(pair.orderFills(idList[0]), 1);
+        assertEq(pair.orderFills(idList[1]), 1);
+    }
+
+    function test_FillAskOrdersByQuote() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 4 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        // two grids, each sells at 5 USDC and 5.05 USDC
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint256 vol0 = pair.calcQuoteAmount(perBaseAmt, sellPrice0);
+        uint256 vol1 = pair.calcQuoteAmount(perBaseAmt, sellPrice0 + gap);
+        uint256 cost = vol0 + (vol0 * 500) / 1000000 + vol1 + (vol1 * 500) / 1000000;
+
+        // quote is more than both levels of grid 1
+        uint64[] memory idList = new uint64[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrdersByQuote(idList, 2000 * 10 ** 6, 0);
+        assertEq(sea.balanceOf(taker), 2 * perBaseAmt);
+        assertEq(usdc.balanceOf(taker), 10000 * 10 ** 6 - cost);
+
+        // quote is less than both levels of grid 2, the second level is partially filled
+        idList[0] = 0x8000000000000003;
+        idList[1] = 0x8000000000000004;
+        uint256 quoteAmt = vol0 + (vol0 * 500) / 1000000 + 100 * 10 ** 6;
+        vm.expectRevert(IPair.InvalidParam.selector);
+        pair.fillAskOrdersByQuote(idList, type(uint256).max, 0);
+        vm.expectRevert(IPair.NotEnoughToFill.selector);
+        pair.fillAskOrdersByQuote(idList, quoteAmt, 2 * perBaseAmt);
+        uint256 usdcBefore = usdc.balanceOf(taker);
+        pair.fillAskOrdersByQuote(idList, quoteAmt, perBaseAmt);
+        vm.stopPrank();
+
+        uint256 amt1 = (((100 * 10 ** 6 * 1000000) / 1000500) * PRICE_MULTIPLIER) /
+            (sellPrice0 + gap);
+        assertEq(sea.balanceOf(taker), 3 * perBaseAmt + amt1);
+        assertLe(usdcBefore - usdc.balanceOf(taker), quoteAmt);
+        assertEq(pair.getGridOrder(idList[0]).amount, 0);
+        assertEq(pair.getGridOrder(idList[1]).amount, perBaseAmt - amt1);
+    }
+
+    // one amount spans two levels, the batch fill stops once maxAmt is filled
+    function test_FillAskGridOrders_sweepLevels() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 3 * perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 3,
+            bids: 0,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](3);
+        uint256[] memory amtList = new uint256[](3);
+        for (uint64 i = 0; i < 3; i++) {
+            idList[i] = 0x8000000000000001 + i;
+            amtList[i] = type(uint96).max;
+        }
+        uint256 amt = perBaseAmt + perBaseAmt / 2;
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.fillAskOrders(idList, amtList, amt, amt);
+        vm.stopPrank();
+
+        assertEq(sea.balanceOf(taker), amt);
+        assertEq(pair.getGridOrder(idList[0]).amount, 0);
+        assertEq(pair.getGridOrder(idList[1]).amount, perBaseAmt / 2);
+        assertEq(pair.getGridOrder(idList[2]).amount, perBaseAmt);
+    }
+
+    function test_FillGridOrders_priceRange() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 sellPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(maker, 2 * perBaseAmt);
+        usdc.transfer(maker, 10000 * 10 ** 6);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(taker, 10000 * 10 ** 6);
+
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 2,
+            bids: 1,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: sellPrice0,
+            buyPrice0: sellPrice0 - gap,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        sea.approve(address(pair), type(uint96).max);
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        uint64[] memory idList = new uint64[](2);
+        uint256[] memory amtList = new uint256[](2);
+        idList[0] = 0x8000000000000001;
+        idList[1] = 0x8000000000000002;
+        amtList[0] = perBaseAmt / 2;
+        amtList[1] = perBaseAmt / 2;
+
+        vm.startPrank(taker);
+        usdc.approve(address(pair), type(uint96).max);
+        sea.approve(address(pair), type(uint96).max);
+
+        // the second ask is above the max price
+        vm.expectRevert(IPair.PriceOutOfRange.selector);
+        pair.fillAskOrdersInRange(idList, amtList, 0, 0, 0, sellPrice0);
+        pair.fillAskOrdersInRange(
+            idList,
+            amtList,
+            0,
+            0,
+            sellPrice0,
+            sellPrice0 + gap
+        );
+        assertEq(sea.balanceOf(taker), 2 * perBaseAmt);
+
+        uint64[] memory bidIdList = new uint64[](1);
+        uint96[] memory bidAmtList = new uint96[](1);
+        bidIdList[0] = 1;
+        bidAmtList[0] = uint96(perBaseAmt);
+
+        // the bid is below the min price
+        vm.expectRevert(IPair.PriceOutOfRange.selector);
+        pair.fillBidOrdersInRange(bidIdList, bidAmtList, 0, 0, sellPrice0, 0);
+        pair.fillBidOrdersInRange(
+            bidIdList,
+            bidAmtList,
+            0,
+            0,
+            sellPrice0 - gap,
+            0
+        );
+        vm.stopPrank();
+        assertEq(sea.balanceOf(taker), perBaseAmt);
+        assertEq(pair.getGridOrder(1).revAmount, perBaseAmt);
+
+        // a canceled order in the list does not fail the price check
+        uint64[] memory cancelList = new uint64[](1);
+        cancelList[0] = 0x8000000000000002;
+        vm.prank(maker);
+        pair.cancelGridOrders(cancelList);
+        vm.prank(taker);
+        pair.fillAskOrdersInRange(
+            idList,
+            amtList,
+            0,
+            0,
+            sellPrice0,
+            sellPrice0
+        );
+        assertEq(sea.balanceOf(taker), perBaseAmt + perBaseAmt / 2);
+    }
+
+    function test_FillAskGridOrder_03() public {
+        // counter.increment();
+        // assertEq(counter.number(), 1);
+    }
+
+    function test_FillBidGridOrder_01() public {
+        address maker = address(0x111);
+        address taker = address(0x333);
+        uint16 bids = 1;
+
+        uint256 perBaseAmt = 100 * 10 ** 18;
+        uint256 buyPrice0 = (50 * PRICE_MULTIPLIER) / 10 / (10 ** 12);
+        uint256 usdcAmt = (uint256(bids) * perBaseAmt * buyPrice0) /
+            PRICE_MULTIPLIER;
+
+        uint256 gap = (5 * PRICE_MULTIPLIER) / 100 / (10 ** 12);
+        sea.transfer(taker, perBaseAmt);
+        usdc.transfer(maker, usdcAmt);
+
+        // place order
+        vm.startPrank(maker);
+        Pair.GridOrderParam memory param = Pair.GridOrderParam({
+            asks: 0,
+            bids: bids,
+            baseAmount: uint96(perBaseAmt),
+            sellPrice0: buyPrice0 + gap,
+            buyPrice0: buyPrice0,
+            sellGap: gap,
+            buyGap: gap,
+            compound: false,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
+        });
+        usdc.approve(address(pair), type(uint96).max);
+        pair.placeGridOrders(param);
+        vm.stopPrank();
+
+        // fill order
+        vm.startPrank(taker);
+        sea.approve(address(pair), type(uint96).max);
+        uint64 id = 1; // first ask grid order
+        pair.fillBidOrders(id, perBaseAmt, 0, 0);
+        vm.stopPrank();
+
+        Pair.Order memory order = pair.getGridOrder(id);
+        assertEq(order.amount, 0);
+        assertEq(sea.balanceOf(taker), 0);
+        assertEq(sea.balanceOf(address(pair)), perBaseAmt);
+
+        {
+            uint256 filledVol = (perBaseAmt * (buyPrice0)) / PRICE_MULTIPLIER;
+            uint256 fee = (filledVol * uint256(pair.fee())) / 1000000;
+            uint8 feeProtocol = pair.feeProtocol();
+            assertEq(order.revAmount, perBaseAmt);
+            assertEq(pair.protocolFees(), fee / feeProtocol);
+            assertEq(pair.getGridProfits(1), 0);
+            assertEq(pair.getGridFees(1), fee - fee/feeProtocol);
+        }
+
+        uint256 usdcNow = usdc.balanceOf(taker) + pair.getGridProfits(1) + pair.getGridFees(1) + pair.protocolFees() + order.amount;
         assertEq(usdcAmt, usdcNow);
         assertEq(usdcAmt, usdc.balanceOf(taker) + usdc.balanceOf(address(pair)));
     }
@@ -295,7 +3765,11 @@ contract PairTest is Test {
             buyPrice0: buyPrice0,
             sellGap: gap,
             buyGap: gap,
-            compound: true
+            compound: true,
+            amountInQuote: false,
+            makerFee: false,
+            startTime: 0,
+            label: bytes32(0)
         });
         usdc.approve(address(pair), type(uint96).max);
         pair.placeGridOrders(param);
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: GPL-2.0-or-later
+pragma solidity ^0.8.25;
+
+import "./ERC20.sol";
+
+// burns 1% of every transferFrom amount, the recipient receives the rest
+contract FeeOnTransferToken is ERC20 {
+    constructor() ERC20("FOT", "FOT", 18) {
+        _mint(msg.sender, 10000000000000000000000000);
+    }
+
+    function transferFrom(
+        address from,
+        address to,
+        uint256 amount
+    ) public override returns (bool) {
+        uint256 fee = amount / 100;
+        _burn(from, fee);
+        return super.transferFrom(from, to, amount - fee);
+    }
+}
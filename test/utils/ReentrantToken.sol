@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: GPL-2.0-or-later
+pragma solidity ^0.8.25;
+
+import "./ERC20.sol";
+
+// calls target with data once on the next transfer, and records the result
+contract ReentrantToken is ERC20 {
+    address public target;
+    bytes public data;
+
+    bool public success;
+    bytes public result;
+
+    constructor() ERC20("RE", "RE", 18) {
+        _mint(msg.sender, 10000000000000000000000000);
+    }
+
+    function setHook(address _target, bytes calldata _data) external {
+        target = _target;
+        data = _data;
+    }
+
+    function transfer(address to, uint256 amount) public override returns (bool) {
+        address _target = target;
+        if (_target != address(0)) {
+            target = address(0);
+            (success, result) = _target.call(data);
+        }
+        return super.transfer(to, amount);
+    }
+}